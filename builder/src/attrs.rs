@@ -0,0 +1,607 @@
+use proc_macro2::Span;
+use syn::{parse_quote, spanned::Spanned, Attribute, Ident, Lit, Meta, NestedMeta, Visibility};
+
+// how setters and `build` receive the builder, from `#[builder(pattern = "...")]`
+#[derive(Clone, Copy, PartialEq, Default)]
+pub(crate) enum Pattern {
+    // `&mut self`, the setters return `&mut Self`
+    #[default]
+    Mutable,
+    // `self`, the setters return `Self` and `build` moves the fields out
+    Owned,
+    // `&self`, the setters return an updated copy of the builder
+    Immutable,
+}
+
+// `#[builder(setter(...))]`, given on the struct or on a field
+#[derive(Clone, Default)]
+pub(crate) struct SetterAttrs {
+    // `setter(into)`
+    pub into: Option<bool>,
+    // `setter(strip_option = false)`
+    pub strip_option: Option<bool>,
+    // `setter(prefix = "...")`
+    pub prefix: Option<Ident>,
+}
+
+// `#[builder(sub_builder(...))]`, describing the builder of the field's type
+#[derive(Default)]
+pub(crate) struct SubBuilderAttrs {
+    // `sub_builder(name = "...")`, the name of the builder
+    pub name: Option<Ident>,
+    // `sub_builder(build_fn = "...")`, the name of its build method
+    pub build_fn: Option<Ident>,
+    // `sub_builder(pattern = "...")`, how its build method receives it
+    pub pattern: Option<Pattern>,
+}
+
+// options given through `#[builder(...)]` on the deriving struct
+#[derive(Default)]
+pub(crate) struct StructAttrs {
+    // `#[builder(name = "...")]`
+    pub name: Option<Ident>,
+    // `#[builder(constructor = "...")]`
+    pub constructor: Option<Ident>,
+    // `#[builder(default)]`
+    pub default: bool,
+    // `#[builder(typestate)]`
+    pub typestate: Option<Span>,
+    // `#[builder(no_std)]`
+    pub no_std: bool,
+    // `#[builder(derive(...))]`, derived for the builder
+    pub derives: Vec<syn::Path>,
+    // `#[builder(struct_attr(...))]`, attributes of the builder
+    pub struct_attrs: Vec<Meta>,
+    // `#[builder(setter_attr(...))]`, attributes of every setter
+    pub setter_attrs: Vec<Meta>,
+    // `#[builder(doc = "...")]`, documenting the builder
+    pub doc: Option<String>,
+    // `#[builder(merge)]`
+    pub merge: bool,
+    // `#[builder(introspect)]`
+    pub introspect: bool,
+    // `#[builder(try_setter)]`, for every field
+    pub try_setter: bool,
+    // `#[builder(pattern = "...")]`
+    pub pattern: Option<Pattern>,
+    // defaults for the setter options of every field
+    pub setter: SetterAttrs,
+    // `#[builder(vis = "...")]` or `#[builder(private)]`
+    pub vis: Option<Visibility>,
+    // `#[builder(build_fn(name = "..."))]`
+    pub build_fn_name: Option<Ident>,
+    // `#[builder(build_fn(error = "..."))]`
+    pub build_fn_error: Option<syn::Path>,
+    // `#[builder(build_fn(collect_missing))]`
+    pub collect_missing: bool,
+    // `#[builder(build_fn(validate = "..."))]`
+    pub validate: Option<syn::Path>,
+    // `#[builder(build_fn(context = "..."))]`
+    pub context: Option<syn::Type>,
+}
+
+// options given through `#[builder(...)]` on a field
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    // `#[builder(name = "...")]`
+    pub name: Option<Ident>,
+    // `#[builder(vis = "...")]` or `#[builder(private)]`
+    pub vis: Option<Visibility>,
+    // `#[builder(skip)]`
+    pub skip: bool,
+    // `#[builder(try_setter)]`
+    pub try_setter: bool,
+    // `#[builder(sub_builder)]` or `#[builder(sub_builder(...))]`
+    pub sub_builder: Option<SubBuilderAttrs>,
+    // `#[builder(optional)]` or `#[builder(optional = false)]`, and where it
+    // was given
+    pub optional: Option<(bool, Span)>,
+    // `#[builder(each = "...")]`
+    pub each: Option<Ident>,
+    // `#[builder(default)]` or `#[builder(default = "...")]`
+    pub default: Option<syn::Expr>,
+    // `#[builder(default_with = "...")]`
+    pub default_with: Option<syn::Path>,
+    // `#[builder(doc = "...")]`, documenting the setters instead of the doc
+    // comments of the field
+    pub doc: Option<String>,
+    // `#[builder(setter_attr(...))]`, attributes of the setters of the field
+    pub setter_attrs: Vec<Meta>,
+    pub setter: SetterAttrs,
+}
+
+// an option as `(key, what it sets, usage)`, where keys setting the same thing
+// conflict with each other
+type Key = (&'static str, &'static str, &'static str);
+
+// the options accepted on the struct
+const STRUCT_KEYS: &[Key] = &[
+    ("name", "name", "`name = \"...\"`"),
+    ("constructor", "constructor", "`constructor = \"...\"`"),
+    ("default", "default", "`default`"),
+    ("private", "vis", "`private`"),
+    ("vis", "vis", "`vis = \"...\"`"),
+    ("typestate", "typestate", "`typestate`"),
+    ("no_std", "no_std", "`no_std`"),
+    ("derive", "derive", "`derive(...)`"),
+    ("struct_attr", "struct_attr", "`struct_attr(...)`"),
+    ("setter_attr", "setter_attr", "`setter_attr(...)`"),
+    ("doc", "doc", "`doc = \"...\"`"),
+    ("merge", "merge", "`merge`"),
+    ("introspect", "introspect", "`introspect`"),
+    ("try_setter", "try_setter", "`try_setter`"),
+    ("pattern", "pattern", "`pattern = \"...\"`"),
+    ("build_fn", "build_fn", "`build_fn(...)`"),
+    ("setter", "setter", "`setter(...)`"),
+];
+
+const BUILD_FN_KEYS: &[Key] = &[
+    ("name", "name", "`build_fn(name = \"...\")`"),
+    ("error", "error", "`build_fn(error = \"...\")`"),
+    ("validate", "validate", "`build_fn(validate = \"...\")`"),
+    ("context", "context", "`build_fn(context = \"...\")`"),
+    (
+        "collect_missing",
+        "collect_missing",
+        "`build_fn(collect_missing)`",
+    ),
+];
+
+const FIELD_KEYS: &[Key] = &[
+    ("name", "name", "`name = \"...\"`"),
+    ("private", "vis", "`private`"),
+    ("vis", "vis", "`vis = \"...\"`"),
+    ("skip", "skip", "`skip`"),
+    ("optional", "optional", "`optional` or `optional = false`"),
+    (
+        "sub_builder",
+        "sub_builder",
+        "`sub_builder` or `sub_builder(...)`",
+    ),
+    ("each", "each", "`each = \"...\"`"),
+    ("default", "default", "`default` or `default = \"...\"`"),
+    ("default_with", "default", "`default_with = \"...\"`"),
+    ("doc", "doc", "`doc = \"...\"`"),
+    ("try_setter", "try_setter", "`try_setter`"),
+    ("setter", "setter", "`setter(...)`"),
+    ("setter_attr", "setter_attr", "`setter_attr(...)`"),
+];
+
+const SUB_BUILDER_KEYS: &[Key] = &[
+    ("name", "name", "`sub_builder(name = \"...\")`"),
+    ("build_fn", "build_fn", "`sub_builder(build_fn = \"...\")`"),
+    ("pattern", "pattern", "`sub_builder(pattern = \"...\")`"),
+];
+
+// the field options which are about its setters, so conflict with `skip`
+const SETTER_ONLY_KEYS: &[&str] = &[
+    "private",
+    "vis",
+    "try_setter",
+    "sub_builder",
+    "each",
+    "doc",
+    "setter",
+    "setter_attr",
+];
+
+const SETTER_KEYS: &[Key] = &[
+    ("into", "into", "`setter(into)` or `setter(into = false)`"),
+    (
+        "strip_option",
+        "strip_option",
+        "`setter(strip_option)` or `setter(strip_option = false)`",
+    ),
+    ("prefix", "prefix", "`setter(prefix = \"...\")`"),
+];
+
+// the keys given so far in one place where options are accepted, which may be
+// spread over several `#[builder(...)]` attributes
+struct Keys {
+    valid: &'static [Key],
+    seen: Vec<(&'static str, syn::Path)>,
+}
+
+impl Keys {
+    fn new(valid: &'static [Key]) -> Self {
+        Keys {
+            valid,
+            seen: Vec::new(),
+        }
+    }
+
+    // rejects unknown keys, and keys setting something already set. Lists like
+    // `setter(...)` may be repeated, their own keys are checked instead.
+    fn check(&mut self, nested: &NestedMeta) -> syn::Result<()> {
+        let path = match nested {
+            NestedMeta::Meta(meta) => meta.path(),
+            NestedMeta::Lit(lit) => {
+                return Err(syn::Error::new_spanned(lit, "expected an option name"))
+            }
+        };
+        let name = match path.get_ident() {
+            Some(ident) => ident.to_string(),
+            None => return Err(self.unknown(path, &path_to_string(path))),
+        };
+        let &(_, sets, _) = match self.valid.iter().find(|(key, _, _)| *key == name) {
+            Some(key) => key,
+            None => return Err(self.unknown(path, &name)),
+        };
+        if let NestedMeta::Meta(Meta::List(_)) = nested {
+            self.seen.push((sets, path.clone()));
+            return Ok(());
+        }
+        if let Some((_, previous)) = self.seen.iter().find(|(other, _)| *other == sets) {
+            let message = if previous.is_ident(&name) {
+                format!("duplicate `{}` option", name)
+            } else {
+                format!("`{}` conflicts with `{}`", name, path_to_string(previous),)
+            };
+            return Err(syn::Error::new_spanned(path, message));
+        }
+        self.seen.push((sets, path.clone()));
+        Ok(())
+    }
+
+    // the first of `keys` given so far
+    fn find(&self, keys: &[&str]) -> Option<&syn::Path> {
+        self.seen
+            .iter()
+            .map(|(_, path)| path)
+            .find(|path| keys.iter().any(|key| path.is_ident(key)))
+    }
+
+    fn unknown(&self, path: &syn::Path, name: &str) -> syn::Error {
+        let closest = self
+            .valid
+            .iter()
+            .map(|(key, _, _)| (edit_distance(name, key), key))
+            .filter(|(distance, key)| *distance <= 2 && *distance < key.len())
+            .min_by_key(|(distance, _)| *distance);
+        let message = match closest {
+            Some((_, key)) => format!("unknown option `{}`, did you mean `{}`?", name, key),
+            None => {
+                let keys: Vec<_> = self
+                    .valid
+                    .iter()
+                    .map(|(key, _, _)| format!("`{}`", key))
+                    .collect();
+                format!(
+                    "unknown option `{}`, expected one of {}",
+                    name,
+                    keys.join(", "),
+                )
+            }
+        };
+        syn::Error::new_spanned(path, message)
+    }
+
+    // the error for a known key given in the wrong form, e.g. `skip = true`
+    fn malformed(&self, nested: &NestedMeta) -> syn::Error {
+        let usage = match nested {
+            NestedMeta::Meta(meta) => self
+                .valid
+                .iter()
+                .find(|(key, _, _)| meta.path().is_ident(key))
+                .map(|(_, _, usage)| *usage),
+            NestedMeta::Lit(_) => None,
+        };
+        match usage {
+            Some(usage) => syn::Error::new_spanned(nested, format!("expected {}", usage)),
+            None => syn::Error::new_spanned(nested, "unrecognized attribute"),
+        }
+    }
+}
+
+impl StructAttrs {
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut this = StructAttrs::default();
+        let mut keys = Keys::new(STRUCT_KEYS);
+        let mut build_fn_keys = Keys::new(BUILD_FN_KEYS);
+        let mut setter_keys = Keys::new(SETTER_KEYS);
+        for list in builder_lists(attrs)? {
+            for nested in list.nested.iter() {
+                keys.check(nested)?;
+                match nested {
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("default") => {
+                        this.default = true;
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("private") => {
+                        this.vis = Some(Visibility::Inherited);
+                    }
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("name") => {
+                        this.name = Some(lit_str(&nv.lit)?.parse()?);
+                    }
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("doc") => {
+                        this.doc = Some(lit_str(&nv.lit)?.value());
+                    }
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("constructor") => {
+                        this.constructor = Some(lit_str(&nv.lit)?.parse()?);
+                    }
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("vis") => {
+                        this.vis = Some(lit_str(&nv.lit)?.parse()?);
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("typestate") => {
+                        this.typestate = Some(path.span());
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("no_std") => {
+                        this.no_std = true;
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("merge") => {
+                        this.merge = true;
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("introspect") => {
+                        this.introspect = true;
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("try_setter") => {
+                        this.try_setter = true;
+                    }
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("pattern") => {
+                        this.pattern = Some(Pattern::parse(&nv.lit)?);
+                    }
+                    NestedMeta::Meta(Meta::List(inner)) if inner.path.is_ident("build_fn") => {
+                        this.parse_build_fn(inner, &mut build_fn_keys)?;
+                    }
+                    NestedMeta::Meta(Meta::List(inner)) if inner.path.is_ident("setter") => {
+                        this.setter.parse(inner, &mut setter_keys)?;
+                    }
+                    NestedMeta::Meta(Meta::List(inner)) if inner.path.is_ident("derive") => {
+                        for nested in inner.nested.iter() {
+                            match nested {
+                                NestedMeta::Meta(Meta::Path(path)) => {
+                                    this.derives.push(path.clone());
+                                }
+                                _ => {
+                                    return Err(syn::Error::new_spanned(
+                                        nested,
+                                        "expected the path of a derive macro",
+                                    ))
+                                }
+                            }
+                        }
+                    }
+                    NestedMeta::Meta(Meta::List(inner)) if inner.path.is_ident("struct_attr") => {
+                        this.struct_attrs.extend(attr_list(inner)?);
+                    }
+                    NestedMeta::Meta(Meta::List(inner)) if inner.path.is_ident("setter_attr") => {
+                        this.setter_attrs.extend(attr_list(inner)?);
+                    }
+                    _ => return Err(keys.malformed(nested)),
+                }
+            }
+        }
+        Ok(this)
+    }
+
+    fn parse_build_fn(&mut self, list: &syn::MetaList, keys: &mut Keys) -> syn::Result<()> {
+        for nested in list.nested.iter() {
+            keys.check(nested)?;
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("name") => {
+                    self.build_fn_name = Some(lit_str(&nv.lit)?.parse()?);
+                }
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("error") => {
+                    self.build_fn_error = Some(lit_str(&nv.lit)?.parse()?);
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("collect_missing") => {
+                    self.collect_missing = true;
+                }
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("validate") => {
+                    self.validate = Some(lit_str(&nv.lit)?.parse()?);
+                }
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("context") => {
+                    self.context = Some(lit_str(&nv.lit)?.parse()?);
+                }
+                _ => return Err(keys.malformed(nested)),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FieldAttrs {
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut this = FieldAttrs::default();
+        let mut keys = Keys::new(FIELD_KEYS);
+        let mut setter_keys = Keys::new(SETTER_KEYS);
+        let mut sub_builder_keys = Keys::new(SUB_BUILDER_KEYS);
+        for list in builder_lists(attrs)? {
+            for nested in list.nested.iter() {
+                keys.check(nested)?;
+                match nested {
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("each") => {
+                        this.each = Some(lit_str(&nv.lit)?.parse()?);
+                    }
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("vis") => {
+                        this.vis = Some(lit_str(&nv.lit)?.parse()?);
+                    }
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("name") => {
+                        this.name = Some(lit_str(&nv.lit)?.parse()?);
+                    }
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("default") => {
+                        this.default = Some(lit_str(&nv.lit)?.parse()?);
+                    }
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("default_with") => {
+                        this.default_with = Some(lit_str(&nv.lit)?.parse()?);
+                    }
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("doc") => {
+                        this.doc = Some(lit_str(&nv.lit)?.value());
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("default") => {
+                        this.default = Some(parse_quote!(core::default::Default::default()));
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("private") => {
+                        this.vis = Some(Visibility::Inherited);
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => {
+                        this.skip = true;
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("sub_builder") => {
+                        this.sub_builder.get_or_insert_with(Default::default);
+                    }
+                    NestedMeta::Meta(Meta::List(inner)) if inner.path.is_ident("sub_builder") => {
+                        this.sub_builder
+                            .get_or_insert_with(Default::default)
+                            .parse(inner, &mut sub_builder_keys)?;
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("try_setter") => {
+                        this.try_setter = true;
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("optional") => {
+                        this.optional = Some((true, path.span()));
+                    }
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("optional") => {
+                        this.optional = Some((lit_bool(&nv.lit)?, nv.span()));
+                    }
+                    NestedMeta::Meta(Meta::List(inner)) if inner.path.is_ident("setter") => {
+                        this.setter.parse(inner, &mut setter_keys)?;
+                    }
+                    NestedMeta::Meta(Meta::List(inner)) if inner.path.is_ident("setter_attr") => {
+                        this.setter_attrs.extend(attr_list(inner)?);
+                    }
+                    _ => return Err(keys.malformed(nested)),
+                }
+            }
+        }
+        if this.skip {
+            if let Some(path) = keys.find(SETTER_ONLY_KEYS) {
+                return Err(syn::Error::new_spanned(
+                    path,
+                    format!("`{}` conflicts with `skip`", path_to_string(path)),
+                ));
+            }
+        }
+        Ok(this)
+    }
+}
+
+impl Pattern {
+    fn parse(lit: &Lit) -> syn::Result<Self> {
+        let lit = lit_str(lit)?;
+        match lit.value().as_str() {
+            "mutable" => Ok(Pattern::Mutable),
+            "owned" => Ok(Pattern::Owned),
+            "immutable" => Ok(Pattern::Immutable),
+            _ => Err(syn::Error::new_spanned(
+                lit,
+                "expected \"mutable\", \"owned\" or \"immutable\"",
+            )),
+        }
+    }
+}
+
+impl SubBuilderAttrs {
+    fn parse(&mut self, list: &syn::MetaList, keys: &mut Keys) -> syn::Result<()> {
+        for nested in list.nested.iter() {
+            keys.check(nested)?;
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("name") => {
+                    self.name = Some(lit_str(&nv.lit)?.parse()?);
+                }
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("build_fn") => {
+                    self.build_fn = Some(lit_str(&nv.lit)?.parse()?);
+                }
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("pattern") => {
+                    self.pattern = Some(Pattern::parse(&nv.lit)?);
+                }
+                _ => return Err(keys.malformed(nested)),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SetterAttrs {
+    fn parse(&mut self, list: &syn::MetaList, keys: &mut Keys) -> syn::Result<()> {
+        for nested in list.nested.iter() {
+            keys.check(nested)?;
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("prefix") => {
+                    self.prefix = Some(lit_str(&nv.lit)?.parse()?);
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("into") => {
+                    self.into = Some(true);
+                }
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("into") => {
+                    self.into = Some(lit_bool(&nv.lit)?);
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("strip_option") => {
+                    self.strip_option = Some(true);
+                }
+                NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("strip_option") => {
+                    self.strip_option = Some(lit_bool(&nv.lit)?);
+                }
+                _ => return Err(keys.malformed(nested)),
+            }
+        }
+        Ok(())
+    }
+
+    // field-level options take precedence over the struct-level ones
+    pub fn or(self, outer: &SetterAttrs) -> SetterAttrs {
+        SetterAttrs {
+            into: self.into.or(outer.into),
+            strip_option: self.strip_option.or(outer.strip_option),
+            prefix: self.prefix.or_else(|| outer.prefix.clone()),
+        }
+    }
+}
+
+// the argument lists of every `#[builder(...)]` attribute
+fn builder_lists(attrs: &[Attribute]) -> syn::Result<Vec<syn::MetaList>> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("builder"))
+        .map(|attr| match attr.parse_meta()? {
+            Meta::List(list) => Ok(list),
+            meta => Err(syn::Error::new_spanned(meta, "expected `#[builder(...)]`")),
+        })
+        .collect()
+}
+
+// the attributes given as `struct_attr(must_use, allow(dead_code))`, each of
+// which is emitted as `#[...]`
+fn attr_list(list: &syn::MetaList) -> syn::Result<Vec<Meta>> {
+    list.nested
+        .iter()
+        .map(|nested| match nested {
+            NestedMeta::Meta(meta) => Ok(meta.clone()),
+            NestedMeta::Lit(lit) => Err(syn::Error::new_spanned(lit, "expected an attribute")),
+        })
+        .collect()
+}
+
+fn path_to_string(path: &syn::Path) -> String {
+    let segments: Vec<_> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+    segments.join("::")
+}
+
+// the number of single-character insertions, deletions and substitutions
+// turning `a` into `b`, used to suggest the option a typo was meant to be
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = previous[j] + usize::from(ca != *cb);
+            current.push(substitute.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn lit_str(lit: &Lit) -> syn::Result<&syn::LitStr> {
+    match lit {
+        Lit::Str(lit_str) => Ok(lit_str),
+        _ => Err(syn::Error::new_spanned(lit, "expected string literal")),
+    }
+}
+
+fn lit_bool(lit: &Lit) -> syn::Result<bool> {
+    match lit {
+        Lit::Bool(lit_bool) => Ok(lit_bool.value),
+        _ => Err(syn::Error::new_spanned(lit, "expected `true` or `false`")),
+    }
+}
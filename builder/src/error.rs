@@ -0,0 +1,240 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::Ident;
+
+use crate::BuilderStruct;
+
+// generates the error type returned by `build()`, e.g.
+//
+//     pub enum CommandBuilderError {
+//         MissingExecutable,
+//         MissingArgs,
+//         MissingFields(Vec<&'static str>),  // with `build_fn(collect_missing)`
+//         Validation(String),
+//         Nested { path: String, message: String },  // with `sub_builder` fields
+//         DefaultWith { field: &'static str, message: String },  // with `default_with`
+//     }
+//
+// along with `field_path` and `reason`, through which the builder of a
+// `#[builder(sub_builder)]` field reports a nested error as `Nested`.
+//
+// With `#[builder(no_std)]` it only implements `core::error::Error`.
+pub(crate) fn expand(target: &BuilderStruct, mandatory_fields: &[&Ident]) -> TokenStream2 {
+    let BuilderStruct {
+        vis,
+        error_name,
+        builder_name,
+        build_fn,
+        ..
+    } = target;
+    let alloc = target.alloc();
+    let error_trait = if target.attrs.no_std {
+        quote!(core::error::Error)
+    } else {
+        quote!(std::error::Error)
+    };
+    let doc = format!("Error returned by [`{}::{}`].", builder_name, build_fn);
+    let variants: Vec<_> = mandatory_fields
+        .iter()
+        .map(|field| missing_variant(field))
+        .collect();
+    let messages = mandatory_fields
+        .iter()
+        .map(|field| format!("missing field `{}`", unraw(field)));
+    let docs = mandatory_fields
+        .iter()
+        .map(|field| format!("The mandatory field `{}` was never set.", unraw(field)));
+    let names = mandatory_fields.iter().map(|field| unraw(field));
+
+    // only `build_fn(collect_missing)` reports several fields at once, and
+    // without it a field named `fields` may have its own `MissingFields`
+    let (missing_fields, missing_fields_message) = if target.attrs.collect_missing {
+        (
+            quote! {
+                /// Several mandatory fields were never set, in declaration order.
+                MissingFields(#alloc::vec::Vec<&'static str>),
+            },
+            quote! {
+                #error_name::MissingFields(fields) => {
+                    f.write_str("missing fields ")?;
+                    for (i, field) in fields.iter().enumerate() {
+                        if i > 0 {
+                            f.write_str(", ")?;
+                        }
+                        core::write!(f, "`{}`", field)?;
+                    }
+                    core::result::Result::Ok(())
+                }
+            },
+        )
+    } else {
+        (TokenStream2::new(), TokenStream2::new())
+    };
+    // only builders of `#[builder(sub_builder)]` fields report nested errors
+    let nested = if target.fields.iter().any(|f| f.sub_builder.is_some()) {
+        quote! {
+            /// The builder of a nested field failed.
+            Nested {
+                /// The dotted path of the field which failed, e.g. `tls.cert`.
+                path: #alloc::string::String,
+                /// What went wrong with it.
+                message: #alloc::string::String,
+            },
+        }
+    } else {
+        TokenStream2::new()
+    };
+    let (nested_path, nested_reason, nested_message) = if nested.is_empty() {
+        (
+            TokenStream2::new(),
+            TokenStream2::new(),
+            TokenStream2::new(),
+        )
+    } else {
+        (
+            quote! {
+                #error_name::Nested { path, .. } => {
+                    core::option::Option::Some(core::clone::Clone::clone(path))
+                }
+            },
+            quote! {
+                #error_name::Nested { message, .. } => core::clone::Clone::clone(message),
+            },
+            quote! {
+                #error_name::Nested { path, message } => {
+                    core::write!(f, "`{}`: {}", path, message)
+                }
+            },
+        )
+    };
+    // only builders of `#[builder(default_with = "...")]` fields report their
+    // failures
+    let default_with = if target.fields.iter().any(|f| f.default_with.is_some()) {
+        quote! {
+            /// The `default_with` function of an unset field failed.
+            DefaultWith {
+                /// The field which could not be computed.
+                field: &'static str,
+                /// The error returned by the function.
+                message: #alloc::string::String,
+            },
+        }
+    } else {
+        TokenStream2::new()
+    };
+    let (default_with_path, default_with_reason, default_with_message) = if default_with.is_empty()
+    {
+        (
+            TokenStream2::new(),
+            TokenStream2::new(),
+            TokenStream2::new(),
+        )
+    } else {
+        (
+            quote! {
+                #error_name::DefaultWith { field, .. } => {
+                    core::option::Option::Some(core::convert::From::from(*field))
+                }
+            },
+            quote! {
+                #error_name::DefaultWith { message, .. } => core::clone::Clone::clone(message),
+            },
+            quote! {
+                #error_name::DefaultWith { field, message } => {
+                    core::write!(f, "`{}`: {}", field, message)
+                }
+            },
+        )
+    };
+    let missing_reason = if variants.is_empty() {
+        TokenStream2::new()
+    } else {
+        quote! {
+            #(#error_name::#variants)|* => core::convert::From::from("missing field"),
+        }
+    };
+
+    quote! {
+        #[doc = #doc]
+        #[derive(core::fmt::Debug, core::clone::Clone, core::cmp::PartialEq, core::cmp::Eq)]
+        #[allow(dead_code)]
+        #vis enum #error_name {
+            #(
+                #[doc = #docs]
+                #variants,
+            )*
+            #missing_fields
+            /// The built value was rejected by validation.
+            Validation(#alloc::string::String),
+            #nested
+            #default_with
+        }
+
+        impl #error_name {
+            /// The dotted path of the field this error is about, if it is
+            /// about a single field.
+            #vis fn field_path(&self) -> core::option::Option<#alloc::string::String> {
+                match self {
+                    #(
+                        #error_name::#variants => {
+                            core::option::Option::Some(core::convert::From::from(#names))
+                        }
+                    )*
+                    #nested_path
+                    #default_with_path
+                    _ => core::option::Option::None,
+                }
+            }
+
+            /// What went wrong, without the field given by `field_path`.
+            #vis fn reason(&self) -> #alloc::string::String {
+                match self {
+                    #missing_reason
+                    #nested_reason
+                    #default_with_reason
+                    _ => #alloc::string::ToString::to_string(self),
+                }
+            }
+        }
+
+        impl core::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                match self {
+                    #(#error_name::#variants => f.write_str(#messages),)*
+                    #missing_fields_message
+                    #error_name::Validation(message) => f.write_str(message),
+                    #nested_message
+                    #default_with_message
+                }
+            }
+        }
+
+        impl #error_trait for #error_name {}
+    }
+}
+
+// `current_dir` is reported through `MissingCurrentDir`
+pub(crate) fn missing_variant(field: &Ident) -> Ident {
+    format_ident!("Missing{}", camel_case(field))
+}
+
+// `current_dir` becomes `CurrentDir`
+pub(crate) fn camel_case(field: &Ident) -> String {
+    unraw(field)
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            core::iter::once(first).chain(chars).collect::<String>()
+        })
+        .collect()
+}
+
+pub(crate) fn unraw(ident: &Ident) -> String {
+    let name = ident.to_string();
+    match name.strip_prefix("r#") {
+        Some(name) => name.to_owned(),
+        None => name,
+    }
+}
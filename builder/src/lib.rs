@@ -1,233 +1,1336 @@
 use proc_macro::TokenStream;
-use quote::{format_ident, quote};
-use syn::{parse_macro_input, spanned::Spanned, DeriveInput};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{parse_macro_input, parse_quote, DeriveInput, Generics, Ident, Type, Visibility};
+
+mod attrs;
+mod error;
+mod typestate;
+
+use attrs::{FieldAttrs, Pattern, StructAttrs};
 
 #[proc_macro_derive(Builder, attributes(builder))]
 pub fn derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
-    // check mistyped inert attribute on field
-    match input.data {
-        syn::Data::Struct(ref data) => {
-            for field in data.fields.iter() {
-                for attr in field.attrs.iter() {
-                    if attr.path.is_ident("builder") {
-                        match attr.parse_meta().unwrap() {
-                            syn::Meta::List(list) => {
-                                let nested = list.nested.iter().next().unwrap();
-                                match nested {
-                                    syn::NestedMeta::Meta(syn::Meta::NameValue(
-                                        syn::MetaNameValue {
-                                            path,
-                                            lit: syn::Lit::Str(lit_str),
-                                            ..
-                                        },
-                                    )) => {
-                                        if path.is_ident("each") {
-                                            lit_str.value();
-                                        } else {
-                                            let error = syn::Error::new_spanned(
-                                                list,
-                                                "expected `builder(each = \"...\")`",
-                                            );
-                                            return error.to_compile_error().into();
-                                        }
-                                    }
-                                    _ => {
-                                        let error = syn::Error::new(
-                                            nested.span(),
-                                            "unrecognized attribute",
-                                        );
-                                        return error.to_compile_error().into();
-                                    }
-                                }
-                            }
-                            _ => {
-                                let error = syn::Error::new(attr.span(), "unrecognized attribute");
-                                return error.to_compile_error().into();
-                            }
-                        }
+    expand(&input)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+// one field of the deriving struct, as seen by the builder
+struct BuilderField<'a> {
+    // the name of the field in the builder, i.e. `_0` for the first field of a
+    // tuple struct unless `#[builder(name = "...")]` is given
+    ident: Ident,
+    // the name of its setter, `ident` unless `setter(prefix = "...")` is given
+    setter_name: Ident,
+    // how the field is named when constructing the deriving struct
+    member: syn::Member,
+    // the type stored in the builder, i.e. `T` for a field of type `Option<T>`
+    // unless `setter(strip_option = false)` is given
+    ty: &'a Type,
+    // the field is left as `None` when its setter was never called
+    is_optional: bool,
+    // where `#[builder(optional)]` was given, at which the `None` is reported
+    // if the type of the field turns out not to be an `Option`
+    optional_span: Option<Span>,
+    // the stored value is wrapped in `Some` when building
+    strip_option: bool,
+    // `setter(into)`
+    into: bool,
+    // name of the one-at-a-time setter from `#[builder(each = "...")]`
+    each: Option<Ident>,
+    // value used when the setter was never called, from `#[builder(default)]`
+    default: Option<syn::Expr>,
+    // fallible function computing the value instead, from
+    // `#[builder(default_with = "...")]`
+    default_with: Option<syn::Path>,
+    // overrides the visibility of the setters of this field
+    vis: Option<Visibility>,
+    // `#[builder(skip)]`, the field has no setter and is only computed by
+    // `build`
+    skip: bool,
+    // `#[builder(try_setter)]`
+    try_setter: bool,
+    // the builder of the field's type from `#[builder(sub_builder)]`, which is
+    // stored instead of the field
+    sub_builder: Option<SubBuilder>,
+    // the doc comments of the field, or `#[builder(doc = "...")]`, for its
+    // setters
+    docs: TokenStream2,
+    // `#[builder(setter_attr(...))]` of the struct and of the field, for its
+    // setters
+    setter_attrs: TokenStream2,
+}
+
+// the builder of a `#[builder(sub_builder)]` field, whose options cannot be
+// seen from here, so they are repeated through `sub_builder(...)`
+struct SubBuilder {
+    // `TlsBuilder` for a field of type `Tls`, unless `sub_builder(name = "...")`
+    // is given
+    ty: Type,
+    // `build` unless `sub_builder(build_fn = "...")` is given
+    build_fn: Ident,
+    // how its build method receives it, from `sub_builder(pattern = "...")`
+    pattern: Pattern,
+}
+
+// everything known about the deriving struct that the generated code needs,
+// or about one variant when deriving on an enum
+struct BuilderStruct<'a> {
+    name: &'a Ident,
+    generics: &'a Generics,
+    attrs: &'a StructAttrs,
+    // the visibility of the builder and everything generated for it, which is
+    // the one of the deriving type unless overridden
+    vis: &'a Visibility,
+    fields: Vec<BuilderField<'a>>,
+    // what `build` constructs, `Command` or `Shape::Circle`
+    path: TokenStream2,
+    // `builder` or `circle_builder`
+    constructor: Ident,
+    // `CommandBuilder` or `ShapeCircleBuilder`
+    builder_name: Ident,
+    // `CommandBuilderError`
+    error_name: Ident,
+    // `build` unless `build_fn(name = "...")` is given
+    build_fn: Ident,
+    // the variant of an enum the builder is for
+    variant: Option<&'a Ident>,
+}
+
+// the parts of a setter's signature besides its name, receiver and return type
+#[derive(Default)]
+struct SetterSig {
+    generics: TokenStream2,
+    params: TokenStream2,
+    where_clause: TokenStream2,
+    // the error of a fallible setter, which returns the builder in a `Result`
+    // and whose body may return early with `?`
+    error: Option<TokenStream2>,
+}
+
+impl SetterSig {
+    fn params(params: TokenStream2) -> Self {
+        SetterSig {
+            params,
+            ..SetterSig::default()
+        }
+    }
+}
+
+impl BuilderStruct<'_> {
+    // fields without any fallback must be set before calling `build`
+    fn is_mandatory(&self, field: &BuilderField) -> bool {
+        !field.skip
+            && !field.is_optional
+            && field.sub_builder.is_none()
+            && field.default.is_none()
+            && field.default_with.is_none()
+            && !self.attrs.default
+    }
+
+    // the fields which have a setter and are stored in the builder
+    fn stored_fields(&self) -> impl Iterator<Item = &BuilderField<'_>> {
+        self.fields.iter().filter(|f| !f.skip)
+    }
+
+    // the crate `Vec` and `String` come from, the rest of the generated code
+    // only uses `core`
+    fn alloc(&self) -> TokenStream2 {
+        if self.attrs.no_std {
+            quote!(alloc)
+        } else {
+            quote!(std)
+        }
+    }
+
+    // the attributes of the builder struct, i.e. `#[derive(...)]` from
+    // `#[builder(derive(...))]` followed by `#[builder(struct_attr(...))]`, and
+    // the attributes of its marker field. Serde must not expect the marker to
+    // be serialized.
+    fn builder_attrs(&self) -> (TokenStream2, TokenStream2) {
+        let derives = &self.attrs.derives;
+        let struct_attrs = &self.attrs.struct_attrs;
+        let struct_attrs = quote!(#(#[#struct_attrs])*);
+        if derives.is_empty() {
+            return (struct_attrs, TokenStream2::new());
+        }
+        let serde = derives.iter().any(|path| {
+            let last = &path.segments.last().unwrap().ident;
+            last == "Serialize" || last == "Deserialize"
+        });
+        let marker_attrs = if serde {
+            quote!(#[serde(skip)])
+        } else {
+            TokenStream2::new()
+        };
+        (quote!(#[derive(#(#derives),*)] #struct_attrs), marker_attrs)
+    }
+
+    // typestate builders are always passed by value
+    fn pattern(&self) -> Pattern {
+        if self.attrs.typestate.is_some() {
+            return Pattern::Owned;
+        }
+        self.attrs.pattern.unwrap_or_default()
+    }
+
+    // a setter of `field` with its attributes, documented like the field
+    // unless it is given a doc of its own
+    fn setter(
+        &self,
+        field: &BuilderField,
+        name: &Ident,
+        doc: Option<String>,
+        sig: SetterSig,
+        body: impl FnOnce(&Ident) -> TokenStream2,
+    ) -> TokenStream2 {
+        let vis = field.vis.as_ref().unwrap_or(self.vis);
+        let docs = match doc {
+            Some(doc) => quote!(#[doc = #doc]),
+            None => field.docs.clone(),
+        };
+        let setter_attrs = &field.setter_attrs;
+        let setter = self.builder_method(vis, name, sig, body);
+        quote! {
+            #docs
+            #setter_attrs
+            #setter
+        }
+    }
+
+    // a method whose body updates the builder through the binding it is
+    // given, with the receiver and return type required by the pattern
+    fn builder_method(
+        &self,
+        vis: &Visibility,
+        name: &Ident,
+        sig: SetterSig,
+        body: impl FnOnce(&Ident) -> TokenStream2,
+    ) -> TokenStream2 {
+        let SetterSig {
+            generics,
+            params,
+            where_clause,
+            error,
+        } = sig;
+        let builder = Ident::new("builder", Span::mixed_site());
+        let body = body(&builder);
+        let builder_ty = match self.pattern() {
+            Pattern::Mutable => quote!(&mut Self),
+            Pattern::Owned | Pattern::Immutable => quote!(Self),
+        };
+        let (returned, ret) = match &error {
+            Some(error) => (
+                quote!(core::result::Result<#builder_ty, #error>),
+                quote!(core::result::Result::Ok(#builder)),
+            ),
+            None => (builder_ty, quote!(#builder)),
+        };
+        match self.pattern() {
+            Pattern::Mutable => quote! {
+                #vis fn #name #generics(&mut self, #params) -> #returned #where_clause {
+                    let #builder = self;
+                    #body
+                    #ret
+                }
+            },
+            Pattern::Owned => quote! {
+                #vis fn #name #generics(self, #params) -> #returned #where_clause {
+                    let mut #builder = self;
+                    #body
+                    #ret
+                }
+            },
+            Pattern::Immutable => {
+                let fields = self.stored_fields().map(|f| &f.ident);
+                quote! {
+                    #vis fn #name #generics(&self, #params) -> #returned #where_clause {
+                        let mut #builder = Self {
+                            #(#fields: core::clone::Clone::clone(&self.#fields),)*
+                            __marker: core::marker::PhantomData,
+                        };
+                        #body
+                        #ret
                     }
                 }
             }
         }
-        syn::Data::Enum(_) => todo!(),
-        syn::Data::Union(_) => todo!(),
     }
 
+    // how docs link to what `build` constructs, e.g. [`Shape::Circle`]
+    fn doc_link(&self) -> String {
+        match self.variant {
+            Some(variant) => format!("[`{}::{}`]", self.name, variant),
+            None => format!("[`{}`]", self.name),
+        }
+    }
+
+    // the doc comment of the builder struct, unless `#[builder(doc = "...")]`
+    // is given
+    fn builder_doc(&self) -> String {
+        match &self.attrs.doc {
+            Some(doc) => doc.clone(),
+            None => format!("Builder for {}.", self.doc_link()),
+        }
+    }
+
+    // the receiver of `build`, and how it reads a field of the builder
+    fn build_receiver(&self) -> TokenStream2 {
+        match self.pattern() {
+            Pattern::Mutable => quote!(&mut self),
+            Pattern::Owned => quote!(self),
+            Pattern::Immutable => quote!(&self),
+        }
+    }
+
+    // the context parameter of `build_with`, which is not shadowed by the
+    // locals `build` binds for fields of the same name
+    fn context_param(&self) -> Ident {
+        Ident::new("ctx", Span::mixed_site())
+    }
+
+    fn stored(&self, field: &BuilderField) -> TokenStream2 {
+        let ident = &field.ident;
+        match self.pattern() {
+            Pattern::Owned => quote!(self.#ident),
+            Pattern::Mutable | Pattern::Immutable => {
+                quote!(core::clone::Clone::clone(&self.#ident))
+            }
+        }
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let attrs = StructAttrs::from_attrs(&input.attrs)?;
     let name = &input.ident;
+    let vis = attrs.vis.as_ref().unwrap_or(&input.vis);
+    let build_fn = attrs
+        .build_fn_name
+        .clone()
+        .unwrap_or_else(|| format_ident!("build"));
+    // `build_fn(context = "...")` turns `build()` into `build_with(ctx)`
+    let build_fn = match attrs.context {
+        Some(_) => format_ident!("{}_with", build_fn),
+        None => build_fn,
+    };
 
-    // extract  all the fields
-    let (fields, arg_names, types, is_optionals) = match input.data {
-        syn::Data::Struct(ref data) => {
-            let fields = data.fields.iter().map(|field| {
-                let field_name = field.ident.as_ref().unwrap();
-                let field_type = &field.ty;
-                let arg_name = match field.attrs.iter().next() {
-                    Some(attr) => {
-                        if attr.path.is_ident("builder") {
-                            Some(match attr.parse_meta().unwrap() {
-                                syn::Meta::List(list) => {
-                                    let nested = list.nested.iter().next().unwrap();
-                                    match nested {
-                                        syn::NestedMeta::Meta(syn::Meta::NameValue(
-                                            syn::MetaNameValue {
-                                                path,
-                                                lit: syn::Lit::Str(lit_str),
-                                                ..
-                                            },
-                                        )) => {
-                                            if path.is_ident("each") {
-                                                lit_str.value()
-                                            } else {
-                                                todo!()
-                                            }
-                                        }
-                                        _ => todo!(),
-                                    }
-                                }
-                                _ => todo!(),
-                            })
-                        } else {
-                            None
-                        }
-                    }
-                    None => None,
+    match &input.data {
+        syn::Data::Struct(data) => {
+            // `CommandBuilder` is the name of the builder struct.
+            let builder_name = attrs
+                .name
+                .clone()
+                .unwrap_or_else(|| format_ident!("{}Builder", name));
+            let target = BuilderStruct {
+                name,
+                generics: &input.generics,
+                attrs: &attrs,
+                vis,
+                fields: parse_fields(&data.fields, &attrs)?,
+                path: quote!(#name),
+                constructor: attrs
+                    .constructor
+                    .clone()
+                    .unwrap_or_else(|| format_ident!("builder")),
+                error_name: format_ident!("{}Error", builder_name),
+                builder_name,
+                build_fn,
+                variant: None,
+            };
+            expand_target(&target)
+        }
+        // every variant of an enum gets its own builder, `Shape::Circle` is
+        // built through `Shape::circle_builder()` returning a
+        // `ShapeCircleBuilder`
+        syn::Data::Enum(data) => {
+            if attrs.default {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "`#[builder(default)]` is not supported on enums",
+                ));
+            }
+            // every variant needs names of its own
+            if let Some(ident) = attrs.name.as_ref().or(attrs.constructor.as_ref()) {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    "`#[builder(name = \"...\")]` and `#[builder(constructor = \"...\")]` are not supported on enums",
+                ));
+            }
+            let mut expanded = TokenStream2::new();
+            for variant in data.variants.iter() {
+                if let Some(attr) = variant.attrs.iter().find(|a| a.path.is_ident("builder")) {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "`#[builder]` is not supported on enum variants",
+                    ));
+                }
+                let ident = &variant.ident;
+                let target = BuilderStruct {
+                    name,
+                    generics: &input.generics,
+                    attrs: &attrs,
+                    vis,
+                    fields: parse_fields(&variant.fields, &attrs)?,
+                    path: quote!(#name::#ident),
+                    constructor: format_ident!("{}_builder", snake_case(ident)),
+                    builder_name: format_ident!("{}{}Builder", name, ident),
+                    error_name: format_ident!("{}{}BuilderError", name, ident),
+                    build_fn: build_fn.clone(),
+                    variant: Some(ident),
                 };
+                expanded.extend(expand_target(&target)?);
+            }
+            Ok(expanded)
+        }
+        syn::Data::Union(data) => Err(syn::Error::new_spanned(
+            data.union_token,
+            "`Builder` cannot be derived for unions",
+        )),
+    }
+}
 
-                // check if the field is optional
-                let (field_type, is_optional) = match field_type {
-                    syn::Type::Path(p) => {
-                        let s = p.path.segments.iter().next().unwrap();
-
-                        if s.ident == "Option" {
-                            match &s.arguments {
-                                syn::PathArguments::AngleBracketed(a) => {
-                                    match a.args.iter().next().unwrap() {
-                                        syn::GenericArgument::Type(t) => (t, true),
-                                        _ => todo!(),
-                                    }
-                                }
-                                _ => todo!(),
-                            }
-                        } else {
-                            (field_type, false)
-                        }
-                    }
-                    _ => todo!(),
-                };
+fn expand_target(target: &BuilderStruct) -> syn::Result<TokenStream2> {
+    if target.attrs.typestate.is_some() {
+        typestate::expand(target)
+    } else {
+        expand_builder(target)
+    }
+}
+
+fn expand_builder(target: &BuilderStruct) -> syn::Result<TokenStream2> {
+    let BuilderStruct {
+        name,
+        generics,
+        attrs,
+        vis,
+        fields,
+        constructor,
+        builder_name,
+        error_name,
+        build_fn,
+        ..
+    } = target;
+
+    // the builder carries the generics of the deriving struct unchanged
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let field_names: Vec<_> = target.stored_fields().map(|f| &f.ident).collect();
+    let field_types: Vec<_> = target.stored_fields().map(|f| f.stored_ty()).collect();
+
+    let mandatory_fields: Vec<_> = fields
+        .iter()
+        .filter(|f| target.is_mandatory(f))
+        .map(|f| &f.ident)
+        .collect();
+
+    // with `build_fn(collect_missing)` every unset mandatory field is reported
+    // through a single `MissingFields` error before anything is built, which a
+    // field named `fields` would report through a variant of the same name
+    if attrs.collect_missing {
+        if let Some(field) = mandatory_fields
+            .iter()
+            .find(|f| error::missing_variant(f) == "MissingFields")
+        {
+            return Err(syn::Error::new_spanned(
+                field,
+                "a mandatory field named `fields` clashes with the `MissingFields` variant of `build_fn(collect_missing)`, give it a default or rename it with `#[builder(name = \"...\")]`",
+            ));
+        }
+    }
+    let alloc = target.alloc();
+    let missing_names: Vec<_> = mandatory_fields.iter().map(|f| error::unraw(f)).collect();
+    let find_missing = quote! {
+        let mut missing = #alloc::vec::Vec::new();
+        #(
+            if self.#mandatory_fields.is_none() {
+                missing.push(#missing_names);
+            }
+        )*
+    };
+    let check_missing = if attrs.collect_missing {
+        quote! {
+            #find_missing
+            if !missing.is_empty() {
+                return core::result::Result::Err(
+                    core::convert::From::from(#error_name::MissingFields(missing)),
+                );
+            }
+        }
+    } else {
+        TokenStream2::new()
+    };
 
-                // each field is deconstructed into a tuple of (field_name, (field_type, is_optional))
-                ((field_name, arg_name), (field_type, is_optional))
-            });
-            // recruisively deconstruct the fields into a tuple of (field_names, field_types, is_optionals)
-            let (field_info, type_info): (Vec<_>, Vec<_>) = fields.unzip();
-            let (types, is_optionals): (Vec<_>, Vec<_>) = type_info.into_iter().unzip();
-            let (field_names, arg_names): (Vec<_>, Vec<_>) = field_info.into_iter().unzip();
+    let build_receiver = target.build_receiver();
+    let context = attrs.context.as_ref().map(|context| {
+        let ctx = target.context_param();
+        quote!(, #ctx: &#context)
+    });
+    let build_value = build_value(target, |f| field_value(target, f, target.stored(f)));
 
-            (field_names, arg_names, types, is_optionals)
+    // `build_fn(validate = "...")` gets to reject the built value before it is
+    // returned
+    let validate = attrs.validate.as_ref().map(|validate| {
+        quote! {
+            #validate(&built).map_err(#error_name::Validation)?;
         }
-        syn::Data::Enum(_) => todo!(),
-        syn::Data::Union(_) => todo!(),
+    });
+
+    // `build_fn(error = "...")` replaces the generated error type in the
+    // signature of `build`, which converts into it through `From`
+    let error_type = error::expand(target, &mandatory_fields);
+    let build_error = match &attrs.build_fn_error {
+        Some(path) => quote!(#path),
+        None => quote!(#error_name),
     };
 
-    let mandatory_fields = fields
-        .iter()
-        .zip(is_optionals.iter())
-        .filter(|(_, is_optional)| !**is_optional)
-        .map(|(field, _)| field);
+    let setters = target.stored_fields().map(|f| {
+        let ident = &f.ident;
+        let (param, value) = f.setter_param(ident, f.ty);
+        let value = f.store(value);
+        let setter = target.setter(
+            f,
+            &f.setter_name,
+            None,
+            SetterSig::params(param),
+            |builder| {
+                quote! {
+                    #builder.#ident = core::option::Option::Some(#value);
+                }
+            },
+        );
+        let try_setter = try_setter(target, f);
+        let sub_builder_mut = sub_builder_mut(target, f);
+        let unset_methods = unset_methods(target, f);
+        quote! {
+            #setter
+            #try_setter
+            #sub_builder_mut
+            #unset_methods
+        }
+    });
+    let each_setters = target.stored_fields().map(|f| each_setters(target, f));
 
-    let optional_fields = fields
-        .iter()
-        .zip(is_optionals.iter())
-        .filter(|(_, is_optional)| **is_optional)
-        .map(|(field, _)| field);
+    let (builder_attrs, marker_attrs) = target.builder_attrs();
 
-    let vec_args = arg_names
-        .iter()
-        .zip(types.iter())
-        .zip(fields.iter())
-        .filter(|((arg, _), ff)| arg.is_some() && &ff.to_string() != arg.as_ref().unwrap());
-
-    let (args_pair, arg_types): (Vec<_>, Vec<_>) = vec_args
-        .map(|((arg, &tt), &ff)| {
-            let arg = arg.as_ref().unwrap();
-            let tt = match tt {
-                syn::Type::Path(p) => {
-                    let s = p.path.segments.iter().next().unwrap();
-                    if s.ident == "Vec" {
-                        match &s.arguments {
-                            syn::PathArguments::AngleBracketed(a) => {
-                                match a.args.iter().next().unwrap() {
-                                    syn::GenericArgument::Type(t) => t,
-                                    _ => todo!(),
-                                }
-                            }
-                            _ => todo!(),
-                        }
-                    } else {
-                        panic!("expected Vec")
+    // `#[builder(merge)]` adds a method where every field set in `other`
+    // overwrites the one of this builder, e.g. when layering configuration
+    // from several sources
+    let merge = if attrs.merge {
+        let merge = format_ident!("merge");
+        let sig = SetterSig::params(quote!(other: Self));
+        let method = target.builder_method(vis, &merge, sig, |builder| {
+            quote! {
+                #(
+                    if let core::option::Option::Some(value) = other.#field_names {
+                        #builder.#field_names = core::option::Option::Some(value);
                     }
+                )*
+            }
+        });
+        quote! {
+            /// Overwrites every field of this builder which is set in `other`.
+            #method
+        }
+    } else {
+        TokenStream2::new()
+    };
+
+    // `#[builder(introspect)]` tells which mandatory fields are left to set.
+    // The builder of a `sub_builder` field tells about its own.
+    let missing_fields = if attrs.introspect {
+        quote! {
+            /// The mandatory fields which are not set yet, in declaration
+            /// order, for which `build` would fail. Fields built through a
+            /// builder of their own are not included, their builders tell
+            /// which of their fields are missing.
+            #vis fn missing_fields(&self) -> #alloc::vec::Vec<&'static str> {
+                #find_missing
+                missing
+            }
+        }
+    } else {
+        TokenStream2::new()
+    };
+
+    let from_value = from_value(target, quote!(#builder_name #ty_generics), |f| {
+        let ident = &f.ident;
+        if f.strip_option {
+            let value = f.store(quote!(value));
+            quote!(core::option::Option::map(#ident, |value| #value))
+        } else {
+            let value = f.store(quote!(#ident));
+            quote!(core::option::Option::Some(#value))
+        }
+    });
+
+    let builder_doc = target.builder_doc();
+    let constructor_doc = format!(
+        "Creates a builder for {} with no field set.",
+        target.doc_link()
+    );
+    let build_doc = format!(
+        "Builds a new {}, failing if a mandatory field was never set.",
+        target.doc_link(),
+    );
+
+    // the builder holds a marker for the deriving type, as a variant of an enum
+    // need not use all of its generic parameters
+    Ok(quote! {
+        #[doc = #builder_doc]
+        #builder_attrs
+        #vis struct #builder_name #generics #where_clause {
+            #( #field_names: core::option::Option<#field_types>, )*
+            #marker_attrs
+            __marker: core::marker::PhantomData<fn() -> #name #ty_generics>,
+        }
+
+        // a private builder need not have all of its setters used
+        #[allow(dead_code)]
+        impl #impl_generics #builder_name #ty_generics #where_clause {
+            #(#setters)*
+
+            #(#each_setters)*
+
+            #merge
+
+            #missing_fields
+
+            #[doc = #build_doc]
+            #vis fn #build_fn(#build_receiver #context) -> core::result::Result<#name #ty_generics, #build_error> {
+                #check_missing
+                #build_value
+                #validate
+                core::result::Result::Ok(built)
+            }
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #[doc = #constructor_doc]
+            #vis fn #constructor() -> #builder_name #ty_generics {
+                core::default::Default::default()
+            }
+        }
+
+        impl #impl_generics core::default::Default for #builder_name #ty_generics #where_clause {
+            fn default() -> Self {
+                #builder_name {
+                    #(#field_names: core::option::Option::None,)*
+                    __marker: core::marker::PhantomData,
                 }
-                _ => tt,
-            };
-            ((ff, format_ident!("{}", arg)), tt)
-        })
-        .unzip();
+            }
+        }
 
-    let (arg_fields, arg_names): (Vec<_>, Vec<_>) = args_pair.into_iter().unzip();
+        #from_value
 
-    // println!(
-    //     "arg fields: {:#?}, {:#?}, {:#?}",
-    //     arg_fields, arg_names, arg_types,
-    // );
+        #error_type
+    })
+}
 
-    // `CommandBuilder` is the name of the builder struct.
-    let builder_name = format_ident!("{}Builder", name);
+// `impl From<Command> for CommandBuilder`, setting every field of the builder
+// from an existing value, and `Command::to_builder` doing the same for a clone
+// of it. A variant of an enum cannot be turned back into its builder, as the
+// value might be any other variant.
+fn from_value(
+    target: &BuilderStruct,
+    builder_type: TokenStream2,
+    stored: impl Fn(&BuilderField) -> TokenStream2,
+) -> TokenStream2 {
+    if target.variant.is_some() {
+        return TokenStream2::new();
+    }
+    let BuilderStruct {
+        name,
+        generics,
+        vis,
+        path,
+        builder_name,
+        ..
+    } = target;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let members = target.stored_fields().map(|f| &f.member);
+    let idents: Vec<_> = target.stored_fields().map(|f| &f.ident).collect();
+    let values = target.stored_fields().map(stored);
 
-    // impl Command
-    let impl_command = quote! {
-        impl #name {
-            pub fn builder() -> #builder_name {
+    // the bound on `to_builder` has a binder so that it is not rejected as
+    // trivially false for a deriving struct which does not implement `Clone`
+    quote! {
+        impl #impl_generics core::convert::From<#name #ty_generics> for #builder_type #where_clause {
+            #[allow(non_shorthand_field_patterns)]
+            fn from(value: #name #ty_generics) -> Self {
+                let #path { #(#members: #idents,)* .. } = value;
                 #builder_name {
-                    #(#fields: None,)*
+                    #(#idents: #values,)*
+                    __marker: core::marker::PhantomData,
                 }
             }
         }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Creates a builder with every field set from a clone of this
+            /// value.
+            #vis fn to_builder(&self) -> #builder_type
+            where
+                for<'__a> Self: core::clone::Clone,
+            {
+                core::convert::From::from(core::clone::Clone::clone(self))
+            }
+        }
+    }
+}
+
+// `#[builder(try_setter)]` adds a setter taking anything convertible into the
+// field through `TryInto`, which returns the error of a failed conversion
+// instead of the builder, e.g.
+//
+//     pub fn try_addr<__V: TryInto<SocketAddr>>(
+//         &mut self,
+//         addr: __V,
+//     ) -> Result<&mut Self, __V::Error>
+fn try_setter(target: &BuilderStruct, field: &BuilderField) -> TokenStream2 {
+    if !field.try_setter {
+        return TokenStream2::new();
+    }
+    let ident = &field.ident;
+    let ty = field.ty;
+    let name = format_ident!("try_{}", error::unraw(&field.setter_name));
+    let sig = SetterSig {
+        generics: quote!(<__V>),
+        params: quote!(#ident: __V),
+        where_clause: quote!(where __V: core::convert::TryInto<#ty>),
+        error: Some(quote!(<__V as core::convert::TryInto<#ty>>::Error)),
+    };
+    let value = field.store(quote!(core::convert::TryInto::try_into(#ident)?));
+    target.setter(field, &name, Some(field.try_setter_doc()), sig, |builder| {
+        quote! {
+            #builder.#ident = core::option::Option::Some(#value);
+        }
+    })
+}
+
+// `#[builder(sub_builder)]` adds a method giving access to the builder of the
+// field, which is created on first use, e.g.
+//
+//     pub fn tls_mut(&mut self) -> &mut TlsConfigBuilder
+fn sub_builder_mut(target: &BuilderStruct, field: &BuilderField) -> TokenStream2 {
+    let sub_builder = match &field.sub_builder {
+        Some(sub_builder) => &sub_builder.ty,
+        None => return TokenStream2::new(),
+    };
+    let ident = &field.ident;
+    let vis = field.vis.as_ref().unwrap_or(target.vis);
+    let doc = format!(
+        "The builder of `{}`, created on first use.",
+        error::unraw(ident),
+    );
+    let setter_attrs = &field.setter_attrs;
+    let name = format_ident!("{}_mut", error::unraw(ident));
+    quote! {
+        #[doc = #doc]
+        #setter_attrs
+        #vis fn #name(&mut self) -> &mut #sub_builder {
+            self.#ident.get_or_insert_with(core::default::Default::default)
+        }
+    }
+}
+
+// `#[builder(introspect)]` gives every stored field a method setting it back to
+// unset, and another telling whether it is set, e.g.
+//
+//     pub fn clear_current_dir(&mut self) -> &mut Self
+//     pub fn current_dir_is_set(&self) -> bool
+fn unset_methods(target: &BuilderStruct, field: &BuilderField) -> TokenStream2 {
+    if !target.attrs.introspect {
+        return TokenStream2::new();
+    }
+    let ident = &field.ident;
+    let vis = field.vis.as_ref().unwrap_or(target.vis);
+    let clear = format_ident!("clear_{}", error::unraw(ident));
+    let is_set = format_ident!("{}_is_set", error::unraw(ident));
+    let clear_doc = format!("Unsets `{}`, as if it was never set.", error::unraw(ident));
+    let clear_setter = target.setter(
+        field,
+        &clear,
+        Some(clear_doc),
+        SetterSig::default(),
+        |builder| {
+            quote! {
+                #builder.#ident = core::option::Option::None;
+            }
+        },
+    );
+    let is_set_doc = format!("Whether `{}` is set.", error::unraw(ident));
+    quote! {
+        #clear_setter
+
+        #[doc = #is_set_doc]
+        #vis fn #is_set(&self) -> bool {
+            core::option::Option::is_some(&self.#ident)
+        }
+    }
+}
+
+// `#[builder(each = "...")]` adds a setter extending the collection one item at
+// a time, and another extending it from an iterator, e.g.
+//
+//     pub fn arg(&mut self, arg: String) -> &mut Self
+//     pub fn extend_args(&mut self, args: impl IntoIterator<Item = String>) -> &mut Self
+//
+// The one-at-a-time setter is skipped if it would have the same name as the
+// all-at-once setter. Neither is affected by `setter(prefix = "...")`.
+fn each_setters(target: &BuilderStruct, field: &BuilderField) -> TokenStream2 {
+    let each = match &field.each {
+        Some(each) => each,
+        None => return TokenStream2::new(),
+    };
+    let ident = &field.ident;
+    let ty = field.ty;
+    let extend = format_ident!("extend_{}", error::unraw(ident));
+    let extend_with = |builder: &Ident, items: TokenStream2| {
+        quote! {
+            core::iter::Extend::extend(
+                #builder.#ident.get_or_insert_with(core::default::Default::default),
+                #items,
+            );
+        }
     };
 
-    // impl CommandBuilder
-    let mut impl_builder = quote! {
-        pub struct #builder_name {
-            #( #fields: std::option::Option<#types>, )*
+    let (each_sig, each_items, extend_sig, extend_items) = match each_item(ty) {
+        EachItem::Value(item_ty) => {
+            let (param, value) = field.setter_param(each, item_ty);
+            let (items_ty, items) = if field.into {
+                (
+                    quote!(impl core::iter::IntoIterator<Item = impl core::convert::Into<#item_ty>>),
+                    quote!(core::iter::Iterator::map(
+                        core::iter::IntoIterator::into_iter(#ident),
+                        core::convert::Into::into,
+                    )),
+                )
+            } else {
+                (
+                    quote!(impl core::iter::IntoIterator<Item = #item_ty>),
+                    quote!(#ident),
+                )
+            };
+            (
+                SetterSig::params(param),
+                quote!(core::iter::once(#value)),
+                SetterSig::params(quote!(#ident: #items_ty)),
+                items,
+            )
         }
-        impl #builder_name {
-            #(pub fn #fields(&mut self, #fields: #types) -> &mut Self {
-                self.#fields = std::option::Option::Some(#fields);
-                self
-            })*
+        EachItem::Entry(key_ty, value_ty) => {
+            let key_name = Ident::new("key", Span::call_site());
+            let value_name = Ident::new("value", Span::call_site());
+            let (key_param, key) = field.setter_param(&key_name, key_ty);
+            let (value_param, value) = field.setter_param(&value_name, value_ty);
+            let (items_ty, items) = if field.into {
+                (
+                    quote! {
+                        impl core::iter::IntoIterator<
+                            Item = (impl core::convert::Into<#key_ty>, impl core::convert::Into<#value_ty>),
+                        >
+                    },
+                    quote!(core::iter::Iterator::map(
+                        core::iter::IntoIterator::into_iter(#ident),
+                        |(key, value)| (core::convert::Into::into(key), core::convert::Into::into(value)),
+                    )),
+                )
+            } else {
+                (
+                    quote!(impl core::iter::IntoIterator<Item = (#key_ty, #value_ty)>),
+                    quote!(#ident),
+                )
+            };
+            (
+                SetterSig::params(quote!(#key_param, #value_param)),
+                quote!(core::iter::once((#key, #value))),
+                SetterSig::params(quote!(#ident: #items_ty)),
+                items,
+            )
+        }
+        EachItem::Any => (
+            SetterSig {
+                generics: quote!(<__T>),
+                params: quote!(#each: __T),
+                where_clause: quote!(where #ty: core::iter::Extend<__T>),
+                error: None,
+            },
+            quote!(core::iter::once(#each)),
+            SetterSig {
+                generics: quote!(<__I: core::iter::IntoIterator>),
+                params: quote!(#ident: __I),
+                where_clause: quote!(where #ty: core::iter::Extend<__I::Item>),
+                error: None,
+            },
+            quote!(#ident),
+        ),
+    };
+
+    let each_setter = if *each != field.setter_name {
+        target.setter(field, each, None, each_sig, |builder| {
+            extend_with(builder, each_items)
+        })
+    } else {
+        TokenStream2::new()
+    };
+    let extend_setter = target.setter(field, &extend, None, extend_sig, |builder| {
+        extend_with(builder, extend_items)
+    });
+    quote! {
+        #each_setter
+        #extend_setter
+    }
+}
 
-            #(pub fn #arg_names(&mut self, #arg_names: #arg_types) -> &mut Self {
-                if (self.#arg_fields.is_none()) {
-                    self.#arg_fields = std::option::Option::Some(Vec::new());
+// what the one-at-a-time setter of a collection takes
+enum EachItem<'a> {
+    // `Vec<T>`, `VecDeque<T>`, `HashSet<T>`, ... take a `T`
+    Value(&'a Type),
+    // `HashMap<K, V>` and `BTreeMap<K, V>` take a key and a value
+    Entry(&'a Type, &'a Type),
+    // any other `Extend + Default` type takes whatever it can be extended with
+    Any,
+}
+
+fn each_item(ty: &Type) -> EachItem<'_> {
+    const VALUES: &[&str] = &[
+        "Vec",
+        "VecDeque",
+        "LinkedList",
+        "BinaryHeap",
+        "HashSet",
+        "BTreeSet",
+    ];
+    const ENTRIES: &[&str] = &["HashMap", "BTreeMap"];
+
+    match type_arguments(ty) {
+        Some((ident, args)) if VALUES.iter().any(|name| ident == name) && !args.is_empty() => {
+            EachItem::Value(args[0])
+        }
+        Some((ident, args)) if ENTRIES.iter().any(|name| ident == name) && args.len() >= 2 => {
+            EachItem::Entry(args[0], args[1])
+        }
+        _ => EachItem::Any,
+    }
+}
+
+// the statements of `build` computing every field into a local variable named
+// after it and constructing `built` from them. Skipped fields are computed
+// last, so that their default may refer to the other fields.
+fn build_value(
+    target: &BuilderStruct,
+    value: impl Fn(&BuilderField) -> TokenStream2,
+) -> TokenStream2 {
+    let path = &target.path;
+    let fields = target
+        .stored_fields()
+        .chain(target.fields.iter().filter(|f| f.skip));
+    let bindings = fields.map(|f| {
+        let ident = &f.ident;
+        let value = value(f);
+        quote!(let #ident = #value;)
+    });
+    let members = target.fields.iter().map(|f| &f.member);
+    let idents = target.fields.iter().map(|f| &f.ident);
+
+    // `#[builder(default)]` on the struct seeds unset fields from its own
+    // `Default` impl
+    let struct_default = if target.attrs.default {
+        let name = target.name;
+        let (_, ty_generics, _) = target.generics.split_for_impl();
+        quote! {
+            let __default = <#name #ty_generics as core::default::Default>::default();
+        }
+    } else {
+        TokenStream2::new()
+    };
+
+    quote! {
+        #struct_default
+        #(#bindings)*
+        let built = #path {
+            #(#members: #idents,)*
+        };
+    }
+}
+
+// the value of a field in `build`, falling back to the field's default when
+// its setter was never called
+fn field_value(target: &BuilderStruct, field: &BuilderField, stored: TokenStream2) -> TokenStream2 {
+    let ident = &field.ident;
+    let member = &field.member;
+    let error_name = &target.error_name;
+    let fallback = if let Some(default) = &field.default {
+        quote!(#default)
+    } else if let Some(default_with) = &field.default_with {
+        // the function is given the context of `build_with`, if any
+        let ctx = target
+            .attrs
+            .context
+            .as_ref()
+            .map(|_| target.context_param());
+        let name = error::unraw(ident);
+        quote! {
+            match #default_with(#ctx) {
+                core::result::Result::Ok(value) => value,
+                core::result::Result::Err(message) => {
+                    return core::result::Result::Err(core::convert::From::from(
+                        #error_name::DefaultWith {
+                            field: #name,
+                            message,
+                        },
+                    ));
                 }
+            }
+        }
+    } else if target.attrs.default {
+        quote!(__default.#member)
+    } else if field.skip {
+        quote!(core::default::Default::default())
+    } else if field.is_optional {
+        let span = field.optional_span.unwrap_or_else(Span::call_site);
+        quote_spanned!(span=> core::option::Option::None)
+    } else {
+        let variant = error::missing_variant(ident);
+        quote! {
+            return core::result::Result::Err(core::convert::From::from(#error_name::#variant))
+        }
+    };
+    if field.skip {
+        return fallback;
+    }
+    if let Some(sub_builder) = &field.sub_builder {
+        return sub_builder_value(target, field, sub_builder, fallback);
+    }
+
+    let value = if field.strip_option {
+        quote!(core::option::Option::Some(value))
+    } else {
+        quote!(value)
+    };
+    quote! {
+        match #stored {
+            core::option::Option::Some(value) => #value,
+            core::option::Option::None => #fallback,
+        }
+    }
+}
 
-                self.#arg_fields.as_mut().unwrap().push(#arg_names);
-                self
-            })*
+// the value of a `#[builder(sub_builder)]` field in `build`, which builds the
+// nested builder even if it was never used, unless the field has a default or
+// is optional. Its errors are reported through the `Nested` variant, whose
+// path leads from this field to the one which failed.
+fn sub_builder_value(
+    target: &BuilderStruct,
+    field: &BuilderField,
+    sub_builder: &SubBuilder,
+    fallback: TokenStream2,
+) -> TokenStream2 {
+    let ident = &field.ident;
+    let error_name = &target.error_name;
+    let alloc = target.alloc();
+    let path = error::unraw(ident);
+    let SubBuilder {
+        ty: sub_builder_ty,
+        build_fn,
+        pattern,
+    } = sub_builder;
 
-            pub fn build(&mut self) -> std::result::Result<Command, std::boxed::Box<dyn std::error::Error>> {
-                std::result::Result::Ok(Command {
-                    #(#mandatory_fields: self.#mandatory_fields.to_owned().ok_or(
-                        format!("missing field `{}`", stringify!(#mandatory_fields))
-                    )?,)*
-                    #(#optional_fields: self.#optional_fields.to_owned(),)*
-                })
+    // the nested builder is built in place if this builder's `build` can give
+    // it the receiver its build method takes, and from a clone of it otherwise
+    let stored = match target.pattern() {
+        Pattern::Owned => quote!(self.#ident),
+        Pattern::Mutable => quote!(core::option::Option::as_mut(&mut self.#ident)),
+        Pattern::Immutable => quote!(core::option::Option::as_ref(&self.#ident)),
+    };
+    let in_place = match pattern {
+        Pattern::Immutable => true,
+        Pattern::Mutable => target.pattern() != Pattern::Immutable,
+        Pattern::Owned => target.pattern() == Pattern::Owned,
+    };
+    let sub = if in_place {
+        quote!(sub)
+    } else {
+        quote_spanned! {ident.span()=>
+            <#sub_builder_ty as core::clone::Clone>::clone(sub)
+        }
+    };
+    let value = if field.strip_option {
+        quote!(core::option::Option::Some(value))
+    } else {
+        quote!(value)
+    };
+    // the nested error tells what failed through `field_path` and `reason`,
+    // which a `build_fn(error = "...")` type has to provide itself
+    let field_path = quote_spanned!(ident.span()=> error.field_path());
+    let reason = quote_spanned!(ident.span()=> error.reason());
+    let build = |sub: TokenStream2| {
+        quote! {
+            match {
+                #[allow(unused_mut)]
+                let mut sub = #sub;
+                sub.#build_fn()
+            } {
+                core::result::Result::Ok(value) => #value,
+                core::result::Result::Err(error) => {
+                    let path = match #field_path {
+                        core::option::Option::Some(path) => #alloc::format!("{}.{}", #path, path),
+                        core::option::Option::None => core::convert::From::from(#path),
+                    };
+                    return core::result::Result::Err(core::convert::From::from(#error_name::Nested {
+                        path,
+                        message: #reason,
+                    }));
+                }
             }
         }
     };
+    let built = build(sub);
+    let fallback = if field.default.is_some()
+        || field.default_with.is_some()
+        || target.attrs.default
+        || field.is_optional
+    {
+        fallback
+    } else {
+        build(quote!(<#sub_builder_ty as core::default::Default>::default()))
+    };
+    quote! {
+        match #stored {
+            core::option::Option::Some(sub) => #built,
+            core::option::Option::None => #fallback,
+        }
+    }
+}
+
+impl BuilderField<'_> {
+    // the type stored in the builder for this field, wrapped in an `Option`
+    fn stored_ty(&self) -> &Type {
+        match &self.sub_builder {
+            Some(sub_builder) => &sub_builder.ty,
+            None => self.ty,
+        }
+    }
+
+    // the docs of `try_<field>`
+    fn try_setter_doc(&self) -> String {
+        format!(
+            "Sets `{}` from a value converting into it, failing if the conversion does.",
+            error::unraw(&self.ident),
+        )
+    }
+
+    // turns a value of the field into what is stored for it
+    fn store(&self, value: TokenStream2) -> TokenStream2 {
+        match &self.sub_builder {
+            Some(_) => quote!(core::convert::From::from(#value)),
+            None => value,
+        }
+    }
+
+    // the parameter of a setter taking a `ty`, and the expression turning it
+    // into a `ty`
+    fn setter_param(&self, name: &Ident, ty: &Type) -> (TokenStream2, TokenStream2) {
+        if self.into {
+            (
+                quote!(#name: impl core::convert::Into<#ty>),
+                quote!(core::convert::Into::into(#name)),
+            )
+        } else {
+            (quote!(#name: #ty), quote!(#name))
+        }
+    }
+}
+
+fn parse_fields<'a>(
+    fields: &'a syn::Fields,
+    outer: &StructAttrs,
+) -> syn::Result<Vec<BuilderField<'a>>> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| parse_field(index, field, outer))
+        .collect()
+}
+
+fn parse_field<'a>(
+    index: usize,
+    field: &'a syn::Field,
+    outer: &StructAttrs,
+) -> syn::Result<BuilderField<'a>> {
+    let attrs = FieldAttrs::from_attrs(&field.attrs)?;
+
+    // fields of tuple structs are set through `_0`, `_1`, ... unless renamed
+    let (ident, member) = match &field.ident {
+        Some(ident) => (
+            attrs.name.clone().unwrap_or_else(|| ident.clone()),
+            syn::Member::Named(ident.clone()),
+        ),
+        None => (
+            attrs
+                .name
+                .clone()
+                .unwrap_or_else(|| format_ident!("_{}", index)),
+            syn::Member::Unnamed(syn::Index::from(index)),
+        ),
+    };
+    let setter = attrs.setter.or(&outer.setter);
+
+    // `setter(prefix = "with")` turns the setter of `args` into `with_args`
+    let setter_name = match &setter.prefix {
+        Some(prefix) => format_ident!("{}_{}", prefix, error::unraw(&ident)),
+        None => ident.clone(),
+    };
+
+    // check if the field is optional. `#[builder(optional)]` marks fields whose
+    // type is an `Option` the derive cannot see, e.g. behind a type alias, and
+    // `#[builder(optional = false)]` fields whose type merely looks like one.
+    let inner_ty = match attrs.optional {
+        Some((false, _)) => None,
+        _ => option_inner_type(&field.ty),
+    };
+    let is_optional = match attrs.optional {
+        Some((optional, _)) => optional,
+        None => inner_ty.is_some(),
+    };
+    let optional_span = match attrs.optional {
+        Some((true, span)) => Some(span),
+        _ => None,
+    };
+    let strip_option = inner_ty.is_some() && setter.strip_option.unwrap_or(true);
+    let ty = match inner_ty {
+        Some(inner) if strip_option => inner,
+        _ => &field.ty,
+    };
+
+    // setters are documented like the field they set, or only say what they
+    // set if the field is undocumented
+    let field_docs: Vec<_> = field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .collect();
+    let docs = match &attrs.doc {
+        Some(doc) => quote!(#[doc = #doc]),
+        None if field_docs.is_empty() => {
+            let doc = format!("Sets `{}`.", error::unraw(&ident));
+            quote!(#[doc = #doc])
+        }
+        None => quote!(#(#field_docs)*),
+    };
+
+    let setter_attrs = outer.setter_attrs.iter().chain(&attrs.setter_attrs);
+    let setter_attrs = quote!(#(#[#setter_attrs])*);
+
+    // `#[builder(sub_builder)]` stores the builder of the field's type, which is
+    // named after it like any other builder
+    let sub_builder = match attrs.sub_builder {
+        Some(_) if attrs.each.is_some() => {
+            return Err(syn::Error::new_spanned(
+                &field.ty,
+                "`sub_builder` and `each` cannot be used together",
+            ));
+        }
+        Some(sub_builder) => Some(SubBuilder {
+            ty: sub_builder_type(ty, sub_builder.name)?,
+            build_fn: sub_builder
+                .build_fn
+                .unwrap_or_else(|| format_ident!("build")),
+            pattern: sub_builder.pattern.unwrap_or_default(),
+        }),
+        None => None,
+    };
+
+    // collections built through `each` start out empty
+    let default = match attrs.default {
+        None if attrs.each.is_some() => Some(parse_quote!(core::default::Default::default())),
+        default => default,
+    };
+
+    Ok(BuilderField {
+        ident,
+        setter_name,
+        member,
+        ty,
+        is_optional,
+        optional_span,
+        strip_option,
+        into: setter.into.unwrap_or(false),
+        each: attrs.each,
+        default,
+        default_with: attrs.default_with,
+        vis: attrs.vis,
+        skip: attrs.skip,
+        try_setter: attrs.try_setter || outer.try_setter,
+        sub_builder,
+        docs,
+        setter_attrs,
+    })
+}
 
-    // combine the two impls tokenstreams
-    impl_builder.extend(impl_command);
-    impl_builder.into()
+// `TlsConfigBuilder` for a field of type `TlsConfig`, or `tls::ConfigBuilder<T>`
+// for `tls::Config<T>`, unless the builder is given another name
+fn sub_builder_type(ty: &Type, name: Option<Ident>) -> syn::Result<Type> {
+    if let syn::Type::Path(p) = ty {
+        if p.qself.is_none() {
+            let mut p = p.clone();
+            let last = p.path.segments.last_mut().unwrap();
+            last.ident = name.unwrap_or_else(|| format_ident!("{}Builder", last.ident));
+            return Ok(syn::Type::Path(p));
+        }
+    }
+    Err(syn::Error::new_spanned(
+        ty,
+        "`sub_builder` requires a field whose type derives `Builder`",
+    ))
+}
+
+// `HttpServer` becomes `http_server`
+fn snake_case(ident: &Ident) -> String {
+    let mut snake = String::new();
+    for (i, ch) in error::unraw(ident).char_indices() {
+        if ch.is_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.extend(ch.to_lowercase());
+        } else {
+            snake.push(ch);
+        }
+    }
+    snake
+}
+
+// the `T` of a type written as `Option<T>`, `std::option::Option<T>` or any
+// other path ending in `Option<T>`
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+    match type_arguments(ty) {
+        Some((ident, args)) if ident == "Option" && args.len() == 1 => Some(args[0]),
+        _ => None,
+    }
+}
+
+// the name of a path type and the types it is given as generic arguments,
+// e.g. `Vec` and `[String]` for `Vec<String>` or `std::vec::Vec<String>`
+fn type_arguments(ty: &Type) -> Option<(&Ident, Vec<&Type>)> {
+    let p = match ty {
+        syn::Type::Path(p) if p.qself.is_none() => p,
+        _ => return None,
+    };
+    let s = p.path.segments.last()?;
+    let args = match &s.arguments {
+        syn::PathArguments::AngleBracketed(a) => a
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::GenericArgument::Type(t) => Some(t),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    Some((&s.ident, args))
 }
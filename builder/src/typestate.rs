@@ -0,0 +1,326 @@
+use crate::attrs::Pattern;
+use crate::{
+    build_value, each_setters, error, field_value, from_value, try_setter, unset_methods,
+    BuilderStruct, SetterSig,
+};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_quote, GenericParam};
+
+// generates a builder which tracks in its type which mandatory fields have
+// been set, so that `build` can only be called once all of them are, e.g.
+//
+//     pub struct CommandBuilder<__Executable = (), __Args = ()> {
+//         executable: __Executable,
+//         args: __Args,
+//         current_dir: Option<String>,
+//         __marker: PhantomData<fn() -> Command>,
+//     }
+//
+// where a mandatory field is stored as `()` until its setter is called and as
+// `(T,)` afterwards. Since the builder changes type with every mandatory
+// setter, it is always passed by value.
+//
+// The builder also holds a marker for the deriving struct, as its generic
+// parameters may only be used by the types of mandatory fields.
+pub(crate) fn expand(target: &BuilderStruct) -> syn::Result<TokenStream2> {
+    let BuilderStruct {
+        name,
+        generics,
+        attrs,
+        vis,
+        fields,
+        constructor,
+        builder_name,
+        build_fn,
+        ..
+    } = target;
+    let span = attrs.typestate.unwrap();
+
+    // a typestate builder cannot fail, so options about the error returned by
+    // `build` make no sense
+    if attrs.build_fn_error.is_some()
+        || attrs.collect_missing
+        || attrs.validate.is_some()
+        || attrs.context.is_some()
+    {
+        return Err(syn::Error::new(
+            span,
+            "`typestate` builders cannot fail, so they do not support `build_fn(error = \"...\")`, `build_fn(validate = \"...\")`, `build_fn(context = \"...\")` or `build_fn(collect_missing)`",
+        ));
+    }
+    if let Some(f) = fields
+        .iter()
+        .find(|f| f.sub_builder.is_some() || f.default_with.is_some())
+    {
+        return Err(syn::Error::new_spanned(
+            &f.ident,
+            "`typestate` builders cannot fail, so they do not support `sub_builder` or `default_with`",
+        ));
+    }
+    // a builder in any state would have to be merged with one in any other
+    if attrs.merge {
+        return Err(syn::Error::new(
+            span,
+            "`typestate` builders do not support `merge`",
+        ));
+    }
+    if matches!(attrs.pattern, Some(pattern) if pattern != Pattern::Owned) {
+        return Err(syn::Error::new(
+            span,
+            "`typestate` builders always use the owned pattern",
+        ));
+    }
+
+    let mandatory: Vec<_> = fields.iter().filter(|f| target.is_mandatory(f)).collect();
+    let optional: Vec<_> = target
+        .stored_fields()
+        .filter(|f| !target.is_mandatory(f))
+        .collect();
+
+    // one type parameter per mandatory field, tracking whether it is set
+    let states: Vec<_> = mandatory
+        .iter()
+        .map(|f| format_ident!("__{}", error::camel_case(&f.ident)))
+        .collect();
+    // `CommandBuilderHasExecutable`, only implemented by the set state
+    let traits: Vec<_> = mandatory
+        .iter()
+        .map(|f| format_ident!("{}Has{}", builder_name, error::camel_case(&f.ident)))
+        .collect();
+
+    let mut builder_generics = (*generics).clone();
+    for state in &states {
+        builder_generics.params.push(parse_quote!(#state = ()));
+    }
+    let (impl_generics, _, where_clause) = builder_generics.split_for_impl();
+    let (struct_impl_generics, ty_generics, _) = generics.split_for_impl();
+
+    // the generic arguments of the deriving struct, followed by the state of
+    // each mandatory field
+    let struct_args: Vec<_> = generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Type(param) => {
+                let ident = &param.ident;
+                quote!(#ident)
+            }
+            GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                quote!(#lifetime)
+            }
+            GenericParam::Const(param) => {
+                let ident = &param.ident;
+                quote!(#ident)
+            }
+        })
+        .collect();
+    let builder_type =
+        |states: &[TokenStream2]| quote!(#builder_name<#(#struct_args,)* #(#states),*>);
+    let current_states: Vec<_> = states.iter().map(|state| quote!(#state)).collect();
+    let self_type = builder_type(&current_states);
+    let unset_states: Vec<_> = states.iter().map(|_| quote!(())).collect();
+    let unset_type = builder_type(&unset_states);
+
+    let mandatory_names: Vec<_> = mandatory.iter().map(|f| &f.ident).collect();
+    let mandatory_types: Vec<_> = mandatory.iter().map(|f| f.ty).collect();
+    let optional_names: Vec<_> = optional.iter().map(|f| &f.ident).collect();
+    let optional_types: Vec<_> = optional.iter().map(|f| f.ty).collect();
+
+    // setting a mandatory field moves every other field into a builder whose
+    // state for that field is `(T,)`
+    let mandatory_setters = mandatory.iter().enumerate().map(|(i, f)| {
+        let ident = &f.ident;
+        let setter_name = &f.setter_name;
+        let docs = &f.docs;
+        let setter_attrs = &f.setter_attrs;
+        let ty = f.ty;
+        let vis = f.vis.as_ref().unwrap_or(vis);
+        let (param, value) = f.setter_param(ident, ty);
+        let mut next_states = current_states.clone();
+        next_states[i] = quote!((#ty,));
+        let next_type = builder_type(&next_states);
+        let others: Vec<_> = mandatory_names
+            .iter()
+            .chain(&optional_names)
+            .filter(|other| **other != ident)
+            .collect();
+        // the fallible setter converts the value before calling the setter
+        let try_setter = if f.try_setter {
+            let try_name = format_ident!("try_{}", error::unraw(setter_name));
+            let try_doc = f.try_setter_doc();
+            quote! {
+                #[doc = #try_doc]
+                #setter_attrs
+                #vis fn #try_name<__V>(
+                    self,
+                    #ident: __V,
+                ) -> core::result::Result<#next_type, <__V as core::convert::TryInto<#ty>>::Error>
+                where
+                    __V: core::convert::TryInto<#ty>,
+                {
+                    let #ident: #ty = core::convert::TryInto::try_into(#ident)?;
+                    core::result::Result::Ok(self.#setter_name(#ident))
+                }
+            }
+        } else {
+            TokenStream2::new()
+        };
+        quote! {
+            #docs
+            #setter_attrs
+            #vis fn #setter_name(self, #param) -> #next_type {
+                #builder_name {
+                    #ident: (#value,),
+                    #(#others: self.#others,)*
+                    __marker: core::marker::PhantomData,
+                }
+            }
+
+            #try_setter
+        }
+    });
+
+    // the remaining fields are stored like in any other builder, so their
+    // setters keep the state unchanged. Whether a mandatory field is set is
+    // part of the type, so only these can be cleared or asked about.
+    let optional_setters = optional.iter().map(|f| {
+        let ident = &f.ident;
+        let (param, value) = f.setter_param(ident, f.ty);
+        let setter = target.setter(
+            f,
+            &f.setter_name,
+            None,
+            SetterSig::params(param),
+            |builder| {
+                quote! {
+                    #builder.#ident = core::option::Option::Some(#value);
+                }
+            },
+        );
+        let try_setter = try_setter(target, f);
+        let each_setters = each_setters(target, f);
+        let unset_methods = unset_methods(target, f);
+        quote! {
+            #setter
+            #try_setter
+            #each_setters
+            #unset_methods
+        }
+    });
+
+    let messages = mandatory.iter().map(|f| {
+        format!(
+            "`{}::{}` requires the mandatory field `{}` to be set",
+            builder_name,
+            build_fn,
+            error::unraw(&f.ident),
+        )
+    });
+    let labels = mandatory
+        .iter()
+        .map(|f| format!("`{}` was never set", error::unraw(&f.ident)));
+    let notes = mandatory.iter().map(|f| {
+        format!(
+            "call `.{}(...)` before `.{}()`",
+            error::unraw(&f.setter_name),
+            build_fn,
+        )
+    });
+
+    // a builder made from an existing value has every mandatory field set
+    let set_states: Vec<_> = mandatory_types.iter().map(|ty| quote!((#ty,))).collect();
+    let from_value = from_value(target, builder_type(&set_states), |f| {
+        let ident = &f.ident;
+        if mandatory_names.contains(&ident) {
+            quote!((#ident,))
+        } else if f.strip_option {
+            quote!(#ident)
+        } else {
+            quote!(core::option::Option::Some(#ident))
+        }
+    });
+
+    let build_value = build_value(target, |f| {
+        let ident = &f.ident;
+        match mandatory_names.iter().position(|name| *name == ident) {
+            Some(i) => {
+                let state_trait = &traits[i];
+                quote!(#state_trait::__value(self.#ident))
+            }
+            None => field_value(target, f, quote!(self.#ident)),
+        }
+    });
+
+    let (builder_attrs, marker_attrs) = target.builder_attrs();
+    let builder_doc = target.builder_doc();
+    let constructor_doc = format!(
+        "Creates a builder for {} with no field set.",
+        target.doc_link(),
+    );
+    let build_doc = format!(
+        "Builds a new {}, once every mandatory field is set.",
+        target.doc_link(),
+    );
+
+    Ok(quote! {
+        #[doc = #builder_doc]
+        #builder_attrs
+        #vis struct #builder_name #builder_generics #where_clause {
+            #( #mandatory_names: #states, )*
+            #( #optional_names: core::option::Option<#optional_types>, )*
+            #marker_attrs
+            __marker: core::marker::PhantomData<fn() -> #name #ty_generics>,
+        }
+
+        #(
+            #[doc(hidden)]
+            #[diagnostic::on_unimplemented(message = #messages, label = #labels, note = #notes)]
+            #vis trait #traits<T> {
+                fn __value(self) -> T;
+            }
+
+            impl<T> #traits<T> for (T,) {
+                fn __value(self) -> T {
+                    self.0
+                }
+            }
+        )*
+
+        #[allow(dead_code)]
+        impl #impl_generics #self_type #where_clause {
+            #(#mandatory_setters)*
+
+            #(#optional_setters)*
+
+            #[doc = #build_doc]
+            #vis fn #build_fn(self) -> #name #ty_generics
+            where
+                #(#states: #traits<#mandatory_types>,)*
+            {
+                #build_value
+                built
+            }
+        }
+
+        impl #struct_impl_generics #name #ty_generics #where_clause {
+            #[doc = #constructor_doc]
+            #vis fn #constructor() -> #unset_type {
+                core::default::Default::default()
+            }
+        }
+
+        impl #struct_impl_generics core::default::Default for #unset_type #where_clause {
+            fn default() -> Self {
+                #builder_name {
+                    #(#mandatory_names: (),)*
+                    #(#optional_names: core::option::Option::None,)*
+                    __marker: core::marker::PhantomData,
+                }
+            }
+        }
+
+        #from_value
+    })
+}
@@ -0,0 +1,45 @@
+// The derive must not assume anything about the name of the struct it is
+// applied to. Deriving Builder on several differently named structs in the
+// same crate should produce independent builders, each of whose `build`
+// method returns its own deriving type.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    args: Vec<String>,
+}
+
+#[derive(Builder)]
+pub struct Server {
+    host: String,
+    port: u16,
+    name: Option<String>,
+}
+
+#[derive(Builder)]
+pub struct Empty {}
+
+fn main() {
+    let command: Command = Command::builder()
+        .executable("cargo".to_owned())
+        .args(vec!["build".to_owned()])
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build"]);
+
+    let server: Server = Server::builder()
+        .host("localhost".to_owned())
+        .port(8080)
+        .build()
+        .unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 8080);
+    assert!(server.name.is_none());
+
+    assert!(Server::builder().port(80).build().is_err());
+
+    let _: Empty = Empty::builder().build().unwrap();
+}
@@ -0,0 +1,59 @@
+// The builder must carry the generics of the deriving struct: lifetimes, type
+// parameters with their bounds, const generics and where-clauses all appear on
+// the generated builder struct, its setters, `build` and the `builder`
+// constructor.
+//
+//     impl<'a, T: Clone, const N: usize> ConfigBuilder<'a, T, N>
+//     where
+//         T: Default,
+//     {
+//         ...
+//     }
+//
+//
+// Resources:
+//
+//   - Splitting generics for use in an impl block:
+//     https://docs.rs/syn/1.0/syn/struct.Generics.html#method.split_for_impl
+
+use derive_builder::Builder;
+use std::fmt::Debug;
+
+#[derive(Builder)]
+pub struct Config<'a, T: Clone, const N: usize>
+where
+    T: Default + Debug,
+{
+    name: &'a str,
+    value: T,
+    values: [u8; N],
+    fallback: Option<T>,
+    #[builder(each = "tag")]
+    tags: Vec<&'a str>,
+}
+
+#[derive(Builder)]
+pub struct Pair<A: Clone, B: Clone = A> {
+    first: A,
+    second: B,
+}
+
+fn main() {
+    let name = String::from("config");
+    let config: Config<u32, 3> = Config::builder()
+        .name(&name)
+        .value(7)
+        .values([1, 2, 3])
+        .tag("a")
+        .tag("b")
+        .build()
+        .unwrap();
+    assert_eq!(config.name, "config");
+    assert_eq!(config.value, 7);
+    assert_eq!(config.values, [1, 2, 3]);
+    assert_eq!(config.fallback, None);
+    assert_eq!(config.tags, vec!["a", "b"]);
+
+    let pair: Pair<u8> = Pair::builder().first(1).second(2).build().unwrap();
+    assert_eq!((pair.first, pair.second), (1, 2));
+}
@@ -0,0 +1,84 @@
+// Instead of a boxed string, `build` returns an error type generated next to
+// the builder which callers can match on. It has one variant per mandatory
+// field plus a `Validation` variant, and implements Display and
+// std::error::Error.
+//
+//     pub enum CommandBuilderError {
+//         MissingExecutable,
+//         MissingCurrentDir,
+//         Validation(String),
+//     }
+//
+// Variants reporting errors of other options, like `Nested` for
+// #[builder(sub_builder)] fields or `DefaultWith` for
+// #[builder(default_with = "...")] fields, only exist if those options are used, so
+// matching on every variant does not need to handle errors which cannot occur.
+//
+// The attribute #[builder(build_fn(error = "..."))] substitutes a type of the
+// caller's choosing in the signature of `build`. That type must implement
+// From<CommandBuilderError>.
+
+use derive_builder::Builder;
+use std::error::Error;
+
+#[derive(Builder, Debug)]
+pub struct Command {
+    executable: String,
+    args: Vec<String>,
+    current_dir: String,
+    env: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Builder(ServerBuilderError),
+}
+
+impl From<ServerBuilderError> for ConfigError {
+    fn from(error: ServerBuilderError) -> Self {
+        ConfigError::Builder(error)
+    }
+}
+
+#[derive(Builder, Debug)]
+#[builder(build_fn(error = "ConfigError"))]
+pub struct Server {
+    r#type: String,
+}
+
+fn main() {
+    let err = Command::builder()
+        .args(vec![])
+        .current_dir("..".to_owned())
+        .build()
+        .unwrap_err();
+    assert_eq!(err, CommandBuilderError::MissingExecutable);
+    assert_eq!(err.to_string(), "missing field `executable`");
+
+    let err = Command::builder()
+        .executable("cargo".to_owned())
+        .args(vec![])
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, CommandBuilderError::MissingCurrentDir));
+
+    let message = match &err {
+        CommandBuilderError::MissingExecutable => "executable",
+        CommandBuilderError::MissingArgs => "args",
+        CommandBuilderError::MissingCurrentDir => "current_dir",
+        CommandBuilderError::Validation(_) => "validation",
+    };
+    assert_eq!(message, "current_dir");
+
+    let boxed: Box<dyn Error> = Box::new(err);
+    assert_eq!(boxed.to_string(), "missing field `current_dir`");
+
+    let err: ConfigError = Server::builder().build().unwrap_err();
+    match err {
+        ConfigError::Builder(ServerBuilderError::MissingType) => {}
+        ConfigError::Builder(other) => panic!("unexpected error: {}", other),
+    }
+
+    let server = Server::builder().r#type("http".to_owned()).build().unwrap();
+    assert_eq!(server.r#type, "http");
+}
@@ -0,0 +1,57 @@
+// By default `build` stops at the first mandatory field that was never set.
+// With #[builder(build_fn(collect_missing))] it instead checks every
+// mandatory field up front and reports all the unset ones together, in
+// declaration order, through the `MissingFields` variant of the error.
+//
+// Without it the error has no `MissingFields` variant, so that a mandatory
+// field named `fields` is reported through one of that name instead.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(build_fn(collect_missing))]
+pub struct Command {
+    executable: String,
+    args: Vec<String>,
+    env: Option<Vec<String>>,
+    current_dir: String,
+}
+
+#[derive(Builder, Debug)]
+pub struct Query {
+    table: String,
+    fields: Vec<String>,
+}
+
+fn main() {
+    let err = Command::builder()
+        .args(vec!["build".to_owned()])
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        CommandBuilderError::MissingFields(vec!["executable", "current_dir"]),
+    );
+    assert_eq!(
+        err.to_string(),
+        "missing fields `executable`, `current_dir`",
+    );
+
+    let err = Command::builder().build().unwrap_err();
+    assert_eq!(
+        err,
+        CommandBuilderError::MissingFields(vec!["executable", "args", "current_dir"]),
+    );
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .args(vec![])
+        .current_dir("..".to_owned())
+        .build()
+        .unwrap();
+    assert!(command.env.is_none());
+
+    let err = Query::builder().table("users".to_owned()).build().unwrap_err();
+    assert_eq!(err, QueryBuilderError::MissingFields);
+    assert_eq!(err.to_string(), "missing field `fields`");
+}
@@ -0,0 +1,70 @@
+// Fields may fall back to a default value when their setter is never called,
+// instead of making `build` fail.
+//
+// #[builder(default)] on a field uses Default::default(), while
+// #[builder(default = "...")] evaluates the given expression. On the struct
+// itself, #[builder(default)] seeds every unset field from the struct's own
+// Default impl. A field-level default takes precedence over the struct-level
+// one.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+pub struct Command {
+    executable: String,
+    #[builder(default)]
+    args: Vec<String>,
+    #[builder(default = "vec![\"PATH=/bin\".to_owned()]")]
+    env: Vec<String>,
+    #[builder(default = "Some(\"..\".to_owned())")]
+    current_dir: Option<String>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(default)]
+pub struct Server {
+    host: String,
+    port: u16,
+    #[builder(default = "4")]
+    workers: usize,
+    name: Option<String>,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Server {
+            host: "localhost".to_owned(),
+            port: 8080,
+            workers: 1,
+            name: Some("default".to_owned()),
+        }
+    }
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .unwrap();
+    assert!(command.args.is_empty());
+    assert_eq!(command.env, vec!["PATH=/bin"]);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .env(vec![])
+        .current_dir("/".to_owned())
+        .build()
+        .unwrap();
+    assert!(command.env.is_empty());
+    assert_eq!(command.current_dir.as_deref(), Some("/"));
+
+    let err = Command::builder().build().unwrap_err();
+    assert_eq!(err, CommandBuilderError::MissingExecutable);
+
+    let server = Server::builder().port(80).build().unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 80);
+    assert_eq!(server.workers, 4);
+    assert_eq!(server.name.as_deref(), Some("default"));
+}
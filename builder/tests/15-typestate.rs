@@ -0,0 +1,59 @@
+// With #[builder(typestate)] the builder records in its type which mandatory
+// fields have been set. Setters take and return the builder by value, and
+// `build` returns the struct directly since it is only callable once every
+// mandatory field has been provided.
+//
+//     pub struct CommandBuilder<__Executable = (), __Args = ()> {
+//         executable: __Executable,
+//         args: __Args,
+//         current_dir: Option<String>,
+//         ...
+//     }
+//
+// A mandatory field is stored as `()` while unset and as `(T,)` once set.
+// Fields which are optional or have a default keep their runtime Option.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    args: Vec<String>,
+    current_dir: Option<String>,
+    #[builder(default = "1")]
+    jobs: u32,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Borrowed<'a, T> {
+    name: &'a str,
+    value: T,
+}
+
+fn main() {
+    let command: Command = Command::builder()
+        .args(vec!["build".to_owned()])
+        .current_dir("..".to_owned())
+        .executable("cargo".to_owned())
+        .build();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build"]);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert_eq!(command.jobs, 1);
+
+    // setting a field twice keeps the last value
+    let command = Command::builder()
+        .executable("rustc".to_owned())
+        .executable("cargo".to_owned())
+        .args(vec![])
+        .jobs(4)
+        .build();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.jobs, 4);
+
+    let name = String::from("answer");
+    let borrowed = Borrowed::builder().value(42).name(&name).build();
+    assert_eq!((borrowed.name, borrowed.value), ("answer", 42));
+}
@@ -0,0 +1,19 @@
+// On a typestate builder, forgetting a mandatory field is a compile error
+// which names the field that still needs to be set, rather than an Err
+// returned from `build` at runtime.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let _command = Command::builder()
+        .executable("cargo".to_owned())
+        .build();
+}
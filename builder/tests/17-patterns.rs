@@ -0,0 +1,69 @@
+// By default setters and `build` borrow the builder mutably, and `build`
+// clones every field out of it. Two other patterns are available through
+// #[builder(pattern = "...")]:
+//
+//   - "owned": setters take and return the builder by value and `build`
+//     moves the fields out, so field types do not need to be Clone.
+//
+//         pub fn executable(self, executable: String) -> Self
+//         pub fn build(self) -> Result<Command, CommandBuilderError>
+//
+//   - "immutable": setters take `&self` and return an updated copy of the
+//     builder, leaving the original untouched.
+//
+//         pub fn executable(&self, executable: String) -> Self
+//         pub fn build(&self) -> Result<Command, CommandBuilderError>
+
+use derive_builder::Builder;
+
+// not Clone
+#[derive(Debug, PartialEq)]
+pub struct Handle(u32);
+
+#[derive(Builder, Debug)]
+#[builder(pattern = "owned")]
+pub struct Connection {
+    handle: Handle,
+    #[builder(each = "peer")]
+    peers: Vec<Handle>,
+    timeout: Option<Handle>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(pattern = "immutable")]
+pub struct Command {
+    executable: String,
+    args: Vec<String>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(pattern = "mutable")]
+pub struct Mutable {
+    value: u8,
+}
+
+fn main() {
+    let connection = Connection::builder()
+        .handle(Handle(1))
+        .peer(Handle(2))
+        .peer(Handle(3))
+        .build()
+        .unwrap();
+    assert_eq!(connection.handle, Handle(1));
+    assert_eq!(connection.peers, vec![Handle(2), Handle(3)]);
+    assert_eq!(connection.timeout, None);
+
+    let err = Connection::builder().peer(Handle(2)).build().unwrap_err();
+    assert_eq!(err, ConnectionBuilderError::MissingHandle);
+
+    let base = Command::builder().executable("cargo".to_owned());
+    let build = base.args(vec!["build".to_owned()]);
+    let test = base.args(vec!["test".to_owned()]);
+    assert!(base.build().is_err());
+    assert_eq!(build.build().unwrap().args, vec!["build"]);
+    assert_eq!(test.build().unwrap().args, vec!["test"]);
+
+    let mut builder = Mutable::builder();
+    builder.value(1);
+    assert_eq!(builder.build().unwrap().value, 1);
+}
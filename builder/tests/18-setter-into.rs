@@ -0,0 +1,67 @@
+// #[builder(setter(into))] makes a setter generic over anything convertible
+// into the field type, so that callers can write `.executable("cargo")`
+// instead of `.executable("cargo".to_owned())`. On the struct it applies to
+// every field, and a field may opt back out with `setter(into = false)`.
+//
+//     pub fn executable(&mut self, executable: impl Into<String>) -> &mut Self
+//
+// Setters of Option<T> fields take a plain T and wrap it in Some. Writing
+// #[builder(setter(strip_option = false))] on such a field makes its setter
+// take the Option<T> itself, which lets callers pass None explicitly.
+
+use derive_builder::Builder;
+use std::path::PathBuf;
+
+#[derive(Builder, Debug)]
+#[builder(setter(into))]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<PathBuf>,
+    #[builder(setter(into = false))]
+    jobs: u32,
+}
+
+#[derive(Builder, Debug)]
+pub struct Server {
+    #[builder(setter(into))]
+    host: String,
+    #[builder(setter(strip_option = false))]
+    name: Option<String>,
+    #[builder(setter(strip_option = false), default = "Some(8080)")]
+    port: Option<u16>,
+    #[builder(setter(into, strip_option = false))]
+    alias: Option<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo")
+        .arg("build")
+        .arg(String::from("--release"))
+        .current_dir("..")
+        .jobs(4)
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.current_dir, Some(PathBuf::from("..")));
+    assert_eq!(command.jobs, 4);
+
+    let server = Server::builder()
+        .host("localhost")
+        .name(Some("local".to_owned()))
+        .port(None)
+        .alias(None)
+        .build()
+        .unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.name.as_deref(), Some("local"));
+    assert_eq!(server.port, None);
+    assert_eq!(server.alias, None);
+
+    let server = Server::builder().host("localhost").build().unwrap();
+    assert_eq!(server.name, None);
+    assert_eq!(server.port, Some(8080));
+}
@@ -0,0 +1,58 @@
+// #[builder(build_fn(validate = "path::to::fn"))] names a function which
+// `build` calls with a reference to the finished struct before returning it.
+// An Err(String) from that function is reported through the `Validation`
+// variant of the builder's error type.
+//
+//     fn validate(command: &Command) -> Result<(), String>
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(build_fn(validate = "checks::validate_command"))]
+pub struct Command {
+    executable: String,
+    #[builder(default)]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+mod checks {
+    pub fn validate_command(command: &super::Command) -> Result<(), String> {
+        if command.current_dir.is_some() && command.args.is_empty() {
+            return Err("`args` must not be empty when `current_dir` is set".to_owned());
+        }
+        Ok(())
+    }
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .unwrap();
+    assert!(command.args.is_empty());
+
+    let err = Command::builder()
+        .executable("cargo".to_owned())
+        .current_dir("..".to_owned())
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        CommandBuilderError::Validation(
+            "`args` must not be empty when `current_dir` is set".to_owned()
+        ),
+    );
+    assert_eq!(
+        err.to_string(),
+        "`args` must not be empty when `current_dir` is set",
+    );
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .args(vec!["build".to_owned()])
+        .current_dir("..".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(command.args, vec!["build"]);
+}
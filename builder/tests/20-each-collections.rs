@@ -0,0 +1,95 @@
+// #[builder(each = "...")] is not limited to Vec. The one-at-a-time setter
+// takes a single item for Vec, VecDeque, LinkedList, BinaryHeap, HashSet and
+// BTreeSet, and a key plus a value for HashMap and BTreeMap. Any other type
+// implementing Extend and Default gets a setter generic over the items it can
+// be extended with.
+//
+// Every field with `each` also gets an `extend_<field>` setter taking an
+// iterator of items, and starts out as an empty collection if neither setter
+// is ever called.
+//
+//     pub fn header(&mut self, key: String, value: String) -> &mut Self
+//     pub fn extend_headers(
+//         &mut self,
+//         headers: impl IntoIterator<Item = (String, String)>,
+//     ) -> &mut Self
+
+use derive_builder::Builder;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Counter(usize);
+
+impl<T> Extend<T> for Counter {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.0 += iter.into_iter().count();
+    }
+}
+
+#[derive(Builder, Debug)]
+pub struct Request {
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(each = "header")]
+    headers: HashMap<String, String>,
+    #[builder(each = "param", setter(into))]
+    params: BTreeMap<String, String>,
+    #[builder(each = "flag")]
+    flags: BTreeSet<char>,
+    #[builder(each = "step")]
+    steps: VecDeque<u32>,
+    #[builder(each = "cookie")]
+    cookies: std::collections::HashMap<String, u32>,
+    #[builder(each = "hit")]
+    hits: Counter,
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg", setter(into))]
+    args: Vec<String>,
+}
+
+fn main() {
+    let request = Request::builder()
+        .arg("a".to_owned())
+        .extend_args(vec!["b".to_owned(), "c".to_owned()])
+        .header("Accept".to_owned(), "*/*".to_owned())
+        .extend_headers(vec![("Host".to_owned(), "localhost".to_owned())])
+        .param("q", "rust")
+        .extend_params(vec![("page", "2")])
+        .flag('v')
+        .flag('v')
+        .flag('a')
+        .step(2)
+        .extend_steps(3..5)
+        .cookie("session".to_owned(), 1)
+        .hit("x")
+        .hit(2)
+        .extend_hits(0..3)
+        .build()
+        .unwrap();
+    assert_eq!(request.args, vec!["a", "b", "c"]);
+    assert_eq!(request.headers.len(), 2);
+    assert_eq!(request.headers["Host"], "localhost");
+    assert_eq!(request.params["q"], "rust");
+    assert_eq!(request.params["page"], "2");
+    assert_eq!(request.flags.into_iter().collect::<String>(), "av");
+    assert_eq!(request.steps, VecDeque::from(vec![2, 3, 4]));
+    assert_eq!(request.cookies["session"], 1);
+    assert_eq!(request.hits, Counter(5));
+
+    let request = Request::builder().build().unwrap();
+    assert!(request.args.is_empty());
+    assert!(request.headers.is_empty());
+    assert_eq!(request.hits, Counter(0));
+
+    let command = Command::builder()
+        .arg("build")
+        .executable("cargo".to_owned())
+        .extend_args(vec!["--release"])
+        .build();
+    assert_eq!(command.args, vec!["build", "--release"]);
+}
@@ -0,0 +1,62 @@
+// Tuple structs get positional setters named `_0`, `_1`, ... A field can be
+// given a more descriptive setter with #[builder(name = "...")].
+//
+// Enums get one builder per variant, created through a constructor named
+// after the variant, whose `build` returns the enum.
+//
+//     impl Shape {
+//         pub fn circle_builder() -> ShapeCircleBuilder { ... }
+//         pub fn rect_builder() -> ShapeRectBuilder { ... }
+//     }
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+pub struct Point(i32, #[builder(name = "y")] i32, Option<i32>);
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Shape<T: Clone> {
+    Circle {
+        radius: T,
+        #[builder(default)]
+        filled: bool,
+    },
+    Rect(#[builder(name = "width")] T, #[builder(name = "height")] T),
+    Empty,
+    HalfPlane(T),
+}
+
+#[derive(Builder)]
+pub struct Unit;
+
+fn main() {
+    let point = Point::builder()._0(1).y(2).build().unwrap();
+    assert_eq!((point.0, point.1, point.2), (1, 2, None));
+
+    let err = Point::builder().y(2).build().unwrap_err();
+    assert_eq!(err, PointBuilderError::Missing0);
+    assert_eq!(err.to_string(), "missing field `_0`");
+
+    let circle = Shape::circle_builder().radius(2.5).build().unwrap();
+    assert_eq!(
+        circle,
+        Shape::Circle {
+            radius: 2.5,
+            filled: false,
+        },
+    );
+
+    let rect = Shape::rect_builder().width(3).height(4).build().unwrap();
+    assert_eq!(rect, Shape::Rect(3, 4));
+
+    let err = Shape::<u8>::rect_builder().width(3).build().unwrap_err();
+    assert_eq!(err, ShapeRectBuilderError::MissingHeight);
+
+    let empty: Shape<u8> = Shape::empty_builder().build().unwrap();
+    assert_eq!(empty, Shape::Empty);
+
+    let half_plane = Shape::half_plane_builder()._0(1).build().unwrap();
+    assert_eq!(half_plane, Shape::HalfPlane(1));
+
+    let _: Unit = Unit::builder().build().unwrap();
+}
@@ -0,0 +1,12 @@
+// A builder for a union would have to pick exactly one field to initialize,
+// which does not fit the derive. Report a clear error instead of panicking.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
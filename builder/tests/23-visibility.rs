@@ -0,0 +1,75 @@
+// The builder, its error type and all of its methods take the visibility of
+// the deriving type, so a private struct does not leak through a public
+// builder. The visibility can be overridden with #[builder(vis = "...")] or
+// #[builder(private)], on the struct for everything generated, or on a field
+// for that field's setters only.
+
+#![deny(warnings)]
+
+mod config {
+    use derive_builder::Builder;
+
+    #[derive(Builder)]
+    struct Secret {
+        key: String,
+    }
+
+    #[derive(Builder, Debug)]
+    pub struct Server {
+        pub host: String,
+        #[builder(private)]
+        pub port: u16,
+        #[builder(vis = "pub(crate)")]
+        pub name: Option<String>,
+    }
+
+    impl ServerBuilder {
+        pub fn local() -> Self {
+            let mut builder = Server::builder();
+            builder.host("localhost".to_owned()).port(8080);
+            builder
+        }
+    }
+
+    #[derive(Builder, Debug)]
+    #[builder(vis = "pub(crate)")]
+    pub struct Client {
+        pub timeout: u32,
+    }
+
+    #[derive(Builder, Debug)]
+    #[builder(private, typestate)]
+    pub struct Internal {
+        pub id: u32,
+    }
+
+    pub fn secret_len() -> usize {
+        let secret = Secret::builder().key("hunter2".to_owned()).build().unwrap();
+        secret.key.len()
+    }
+
+    pub fn internal() -> Internal {
+        Internal::builder().id(7).build()
+    }
+}
+
+use config::{Client, Server, ServerBuilder};
+
+fn main() {
+    let server = ServerBuilder::local()
+        .name("local".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.name.as_deref(), Some("local"));
+
+    let err = Server::builder().build().unwrap_err();
+    assert_eq!(err, config::ServerBuilderError::MissingHost);
+
+    let client = Client::builder().timeout(30).build().unwrap();
+    assert_eq!(client.timeout, 30);
+
+    assert_eq!(config::secret_len(), 7);
+    assert_eq!(config::internal().id, 7);
+}
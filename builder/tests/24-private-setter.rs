@@ -0,0 +1,26 @@
+// A setter made private with #[builder(private)] is only callable from the
+// module of the deriving struct.
+
+mod config {
+    use derive_builder::Builder;
+
+    #[derive(Builder)]
+    pub struct Server {
+        pub host: String,
+        #[builder(private)]
+        pub port: u16,
+    }
+
+    impl ServerBuilder {
+        pub fn default_port(&mut self) -> &mut Self {
+            self.port(8080)
+        }
+    }
+}
+
+fn main() {
+    let _ = config::Server::builder()
+        .host("localhost".to_owned())
+        .port(80)
+        .build();
+}
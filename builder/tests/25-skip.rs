@@ -0,0 +1,52 @@
+// A field marked #[builder(skip)] gets no setter and is not stored in the
+// builder. Instead `build` computes it, from #[builder(default = "...")] if
+// given and otherwise from Default::default().
+//
+// Skipped fields are computed after every other field, so their default
+// expression may refer to the values of the other fields by name.
+
+use derive_builder::Builder;
+use std::marker::PhantomData;
+
+#[derive(Builder, Debug)]
+pub struct Command {
+    executable: String,
+    args: Vec<String>,
+    #[builder(skip, default = "format!(\"{} {}\", executable, args.join(\" \"))")]
+    line: String,
+    #[builder(skip)]
+    runs: u32,
+}
+
+#[derive(Builder)]
+pub struct Tagged<T> {
+    name: String,
+    #[builder(skip)]
+    tag: PhantomData<T>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate)]
+pub struct Request {
+    url: String,
+    #[builder(skip, default = "url.len()")]
+    len: usize,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .args(vec!["build".to_owned(), "--release".to_owned()])
+        .build()
+        .unwrap();
+    assert_eq!(command.line, "cargo build --release");
+    assert_eq!(command.runs, 0);
+    assert_eq!(command.executable, "cargo");
+
+    let tagged: Tagged<u8> = Tagged::builder().name("x".to_owned()).build().unwrap();
+    assert_eq!(tagged.name, "x");
+    let _: PhantomData<u8> = tagged.tag;
+
+    let request = Request::builder().url("http://a".to_owned()).build();
+    assert_eq!(request.len, 8);
+}
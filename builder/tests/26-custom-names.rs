@@ -0,0 +1,52 @@
+// The names of everything the derive generates can be chosen.
+//
+// #[builder(name = "...")] names the builder type, and its error type becomes
+// the builder name followed by `Error`. #[builder(constructor = "...")] names
+// the associated function returning the builder, and
+// #[builder(build_fn(name = "..."))] names the method building the value.
+//
+// #[builder(setter(prefix = "..."))] prefixes the name of every setter, either
+// on the struct or on a single field.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(
+    name = "CommandOptions",
+    constructor = "options",
+    build_fn(name = "finish"),
+    setter(prefix = "with")
+)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate, build_fn(name = "finish"))]
+pub struct Request {
+    #[builder(setter(prefix = "set"))]
+    url: String,
+    method: Option<String>,
+}
+
+fn main() {
+    let mut options: CommandOptions = Command::options();
+    options.with_executable("cargo".to_owned()).arg("build".to_owned());
+    let command = options.finish().unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build"]);
+    assert_eq!(command.current_dir, None);
+
+    let err: CommandOptionsError = Command::options().finish().unwrap_err();
+    assert_eq!(err, CommandOptionsError::MissingExecutable);
+
+    let request = Request::builder()
+        .method("GET".to_owned())
+        .set_url("http://a".to_owned())
+        .finish();
+    assert_eq!(request.url, "http://a");
+    assert_eq!(request.method.as_deref(), Some("GET"));
+}
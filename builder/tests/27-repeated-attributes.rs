@@ -0,0 +1,34 @@
+// Options may be given in any order, and spread over any number of
+// #[builder(...)] attributes, including the options of setter(...) and
+// build_fn(...).
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(setter(into))]
+#[builder(build_fn(name = "finish"), pattern = "owned")]
+#[builder(build_fn(collect_missing), setter(prefix = "with"))]
+pub struct Command {
+    executable: String,
+    #[builder(default)]
+    #[builder(each = "arg", setter(into = false))]
+    args: Vec<String>,
+    #[builder(setter(strip_option = false), private)]
+    #[builder(name = "dir")]
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .with_executable("cargo")
+        .arg("build".to_owned())
+        .with_dir(Some("..".to_owned()))
+        .finish()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build"]);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+
+    let err = Command::builder().finish().unwrap_err();
+    assert_eq!(err, CommandBuilderError::MissingFields(vec!["executable"]));
+}
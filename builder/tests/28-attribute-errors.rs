@@ -0,0 +1,79 @@
+// Mistakes in #[builder(...)] options are reported at the offending option:
+// unknown options suggest the closest valid one if there is one, and options
+// given twice, or together with an option they conflict with, are rejected
+// even when spread over several attributes. A skipped field has no setter, so
+// options about its setters conflict with `skip`.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(patern = "owned")]
+pub struct Typo {
+    executable: String,
+}
+
+#[derive(Builder)]
+pub struct Unknown {
+    #[builder(optional_field)]
+    executable: String,
+}
+
+#[derive(Builder)]
+pub struct Duplicate {
+    #[builder(each = "arg")]
+    #[builder(default, each = "argument")]
+    args: Vec<String>,
+}
+
+#[derive(Builder)]
+#[builder(private, vis = "pub(crate)")]
+pub struct Conflict {
+    executable: String,
+}
+
+#[derive(Builder)]
+#[builder(setter(into), setter(into = false))]
+pub struct DuplicateSetter {
+    executable: String,
+}
+
+#[derive(Builder)]
+#[builder(build_fn(colect_missing))]
+pub struct BuildFnTypo {
+    executable: String,
+}
+
+#[derive(Builder)]
+pub struct Malformed {
+    #[builder(skip = true)]
+    executable: String,
+}
+
+#[derive(Builder)]
+pub struct SkippedSetter {
+    executable: String,
+    #[builder(skip, each = "arg")]
+    args: Vec<String>,
+}
+
+#[derive(Builder)]
+pub struct SkippedSetterAttr {
+    executable: String,
+    #[builder(setter_attr(inline))]
+    #[builder(skip)]
+    args: Vec<String>,
+}
+
+#[derive(Builder)]
+pub struct InvalidName {
+    #[builder(each = "1bad")]
+    args: Vec<String>,
+}
+
+#[derive(Builder)]
+pub struct KeywordName {
+    #[builder(each = "type")]
+    types: Vec<String>,
+}
+
+fn main() {}
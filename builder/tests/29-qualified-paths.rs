@@ -0,0 +1,64 @@
+// Option and the collections supported by `each` are recognized by the last
+// segment of their path, so fully qualified paths like std::option::Option<T>
+// and core::option::Option<T> work the same as a plain Option<T>.
+//
+// The derive cannot see through type aliases, so #[builder(optional)] marks a
+// field as optional whose type is an Option behind an alias. Its setter takes
+// the alias type unchanged. Conversely #[builder(optional = false)] makes a
+// field mandatory whose type merely looks like an Option.
+
+use derive_builder::Builder;
+
+type MaybeStr = Option<String>;
+
+mod custom {
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct Option<T>(pub T);
+}
+
+#[derive(Builder, Debug)]
+pub struct Command {
+    executable: std::string::String,
+    #[builder(each = "arg")]
+    args: std::vec::Vec<String>,
+    #[builder(each = "var")]
+    env: std::collections::HashMap<String, String>,
+    current_dir: std::option::Option<String>,
+    user: core::option::Option<String>,
+    #[builder(optional)]
+    shell: MaybeStr,
+    #[builder(optional = false)]
+    flag: custom::Option<bool>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .var("RUST_LOG".to_owned(), "debug".to_owned())
+        .current_dir("..".to_owned())
+        .shell(Some("bash".to_owned()))
+        .flag(custom::Option(true))
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build"]);
+    assert_eq!(command.env["RUST_LOG"], "debug");
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert_eq!(command.user, None);
+    assert_eq!(command.shell.as_deref(), Some("bash"));
+    assert_eq!(command.flag, custom::Option(true));
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .flag(custom::Option(false))
+        .build()
+        .unwrap();
+    assert_eq!(command.shell, None);
+
+    let err = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .unwrap_err();
+    assert_eq!(err, CommandBuilderError::MissingFlag);
+}
@@ -0,0 +1,69 @@
+// Builders implement Default, starting out with no field set, and can be made
+// from an existing value through `From`, with every field already set. For
+// types implementing Clone, `to_builder()` does the same from a reference, so
+// that patched copies of a value can be built.
+//
+// Skipped fields are not carried over and are computed again by `build`. The
+// builders of enum variants cannot be made from a value, as it might be any
+// other variant.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+pub struct Config {
+    host: String,
+    port: u16,
+    #[builder(each = "tag")]
+    tags: Vec<String>,
+    user: Option<String>,
+    #[builder(skip, default = "format!(\"{}:{}\", host, port)")]
+    address: String,
+}
+
+// `to_builder` is only available for types implementing Clone
+#[derive(Builder, Debug)]
+pub struct Handle(u32, Option<String>);
+
+#[derive(Builder, Clone, Debug)]
+#[builder(typestate)]
+pub struct Request {
+    url: String,
+    method: Option<String>,
+}
+
+fn main() {
+    let base = Config::builder()
+        .host("localhost".to_owned())
+        .port(80)
+        .tag("web".to_owned())
+        .build()
+        .unwrap();
+    let patched = base.to_builder().port(8080).build().unwrap();
+    assert_eq!(patched.host, "localhost");
+    assert_eq!(patched.port, 8080);
+    assert_eq!(patched.tags, vec!["web"]);
+    assert_eq!(patched.user, None);
+    assert_eq!(patched.address, "localhost:8080");
+
+    let mut builder = ConfigBuilder::from(base.clone());
+    assert_eq!(builder.build().unwrap(), base);
+
+    let err = ConfigBuilder::default().build().unwrap_err();
+    assert_eq!(err, ConfigBuilderError::MissingHost);
+
+    let handle = HandleBuilder::from(Handle(7, Some("a".to_owned())))
+        ._1("b".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(handle.0, 7);
+    assert_eq!(handle.1.as_deref(), Some("b"));
+
+    // a builder made from a value can be built right away
+    let request = Request::builder().url("http://a".to_owned()).build();
+    let request = request.to_builder().method("POST".to_owned()).build();
+    assert_eq!(request.url, "http://a");
+    assert_eq!(request.method.as_deref(), Some("POST"));
+
+    let request = RequestBuilder::default().url("http://b".to_owned()).build();
+    assert_eq!(request.url, "http://b");
+}
@@ -0,0 +1,78 @@
+// With #[builder(no_std)] the generated code only refers to `core` and to
+// `alloc`, for the Vec and String used by the error type, so that it compiles
+// in #![no_std] crates declaring `extern crate alloc`. The error type then
+// implements core::error::Error instead of std::error::Error.
+//
+// This test crate only links std from inside the `rt` module, which provides
+// the panic handler of the binary without bringing `std` into scope for the
+// code generated at the crate root.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(no_std, build_fn(collect_missing))]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(no_std, typestate)]
+pub struct Request {
+    url: String,
+    port: Option<u16>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(no_std)]
+pub enum Shape {
+    Circle { radius: u32 },
+    Square(u32),
+}
+
+fn check_error<E: core::error::Error>(_: &E) {}
+
+mod rt {
+    extern crate std;
+
+    pub fn main() {
+        super::run();
+    }
+}
+
+fn run() {
+    let command = Command::builder()
+        .executable("cargo".into())
+        .arg("build".into())
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, ["build"]);
+    assert_eq!(command.current_dir, None);
+
+    let err = Command::builder().build().unwrap_err();
+    assert_eq!(err, CommandBuilderError::MissingFields(alloc::vec!["executable"]));
+    check_error(&err);
+    assert_eq!(alloc::format!("{}", err), "missing fields `executable`");
+
+    let request = Request::builder().url("http://a".into()).build();
+    assert_eq!(request.url, "http://a");
+    assert_eq!(request.port, None);
+
+    let shape = Shape::circle_builder().radius(2).build().unwrap();
+    assert!(matches!(shape, Shape::Circle { radius: 2 }));
+    let shape = Shape::square_builder()._0(3).build().unwrap();
+    assert!(matches!(shape, Shape::Square(3)));
+}
+
+fn main() {
+    rt::main();
+}
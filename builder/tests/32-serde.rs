@@ -0,0 +1,68 @@
+// #[builder(derive(...))] derives the given traits for the builder. With
+// serde's Deserialize, a builder can be loaded from a partial configuration,
+// where every missing field is left unset.
+//
+// #[builder(merge)] adds a `merge` method, where each field set in the other
+// builder overwrites the one of this builder. Together they allow layering
+// configuration from several sources before building it. Like the setters,
+// `merge` takes the builder as given by the pattern.
+//
+//     pub fn merge(&mut self, other: Self) -> &mut Self
+
+use derive_builder::Builder;
+use serde::Deserialize;
+
+#[derive(Builder, Debug)]
+#[builder(derive(Deserialize, Debug), merge)]
+pub struct Config {
+    host: String,
+    port: u16,
+    #[builder(default)]
+    tags: Vec<String>,
+    user: Option<String>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(derive(serde::Deserialize), merge, pattern = "owned")]
+pub struct Pair<T> {
+    first: T,
+    second: T,
+}
+
+// without the option, a field may have a setter named `merge`
+#[derive(Builder, Debug)]
+pub struct Branch {
+    merge: bool,
+}
+
+fn main() {
+    let defaults: ConfigBuilder =
+        serde_json::from_str(r#"{ "host": "localhost", "port": 80 }"#).unwrap();
+    let file: ConfigBuilder =
+        serde_json::from_str(r#"{ "port": 8080, "tags": ["web"] }"#).unwrap();
+    let mut overrides = ConfigBuilder::default();
+    overrides.user("admin".to_owned());
+
+    let config = Config::builder()
+        .merge(defaults)
+        .merge(file)
+        .merge(overrides)
+        .build()
+        .unwrap();
+    assert_eq!(config.host, "localhost");
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.tags, vec!["web"]);
+    assert_eq!(config.user.as_deref(), Some("admin"));
+
+    let partial: ConfigBuilder = serde_json::from_str("{}").unwrap();
+    assert!(format!("{:?}", partial).starts_with("ConfigBuilder"));
+    let mut partial = partial;
+    assert_eq!(partial.build().unwrap_err(), ConfigBuilderError::MissingHost);
+
+    let pair: PairBuilder<u8> = serde_json::from_str(r#"{ "first": 1 }"#).unwrap();
+    let pair = Pair::builder().second(2).merge(pair).build().unwrap();
+    assert_eq!((pair.first, pair.second), (1, 2));
+
+    let branch = Branch::builder().merge(true).build().unwrap();
+    assert!(branch.merge);
+}
@@ -0,0 +1,95 @@
+//! The doc comments of a field are copied onto all of its setters, including
+//! the `each` setters, and #[builder(doc = "...")] on a field replaces them.
+//! Methods doing something else with the field, like `try_<field>`,
+//! `clear_<field>` and `<field>_mut`, say what they do instead.
+//! The builder, its constructor and its other methods get generated docs,
+//! which #[builder(doc = "...")] on the struct replaces for the builder.
+//!
+//! Setters of undocumented fields say which field they set. Nothing generated
+//! is left undocumented, so builders of documented types pass
+//! #![deny(missing_docs)], even when their private fields are undocumented.
+
+#![deny(missing_docs)]
+
+use derive_builder::Builder;
+
+/// A command to run.
+#[derive(Builder, Clone, Debug)]
+#[builder(doc = "Collects the parts of a [`Command`].")]
+pub struct Command {
+    /// The program to run.
+    pub executable: String,
+    /// The arguments passed to the program.
+    #[builder(each = "arg")]
+    pub args: Vec<String>,
+    /// This doc is replaced for the setter.
+    #[builder(doc = "Sets the directory the program runs in.")]
+    pub current_dir: Option<String>,
+}
+
+/// A request to send.
+#[derive(Builder, Debug)]
+#[builder(typestate)]
+pub struct Request {
+    /// Where to send the request.
+    pub url: String,
+    /// How to send the request.
+    pub method: Option<String>,
+}
+
+/// A job whose fields are private.
+#[derive(Builder, Debug)]
+#[builder(merge, try_setter, introspect)]
+pub struct Job {
+    name: String,
+    #[builder(each = "step")]
+    steps: Vec<String>,
+    retries: Option<u32>,
+}
+
+/// A typestate builder whose fields are private.
+#[derive(Builder, Debug)]
+#[builder(typestate)]
+pub struct Task {
+    name: String,
+    priority: Option<u8>,
+}
+
+/// A shape to draw.
+#[derive(Builder, Debug)]
+pub enum Shape {
+    /// A circle.
+    Circle {
+        /// The radius of the circle.
+        radius: u32,
+    },
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(command.args, vec!["build"]);
+
+    let request = Request::builder().url("http://a".to_owned()).build();
+    assert_eq!(request.method, None);
+
+    let job = Job::builder()
+        .name("ci".to_owned())
+        .step("test".to_owned())
+        .try_retries(3u8)
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(job.steps, vec!["test"]);
+    assert_eq!(job.retries, Some(3));
+
+    let task = Task::builder().name("lint".to_owned()).priority(1).build();
+    assert_eq!(task.name, "lint");
+    assert_eq!(task.priority, Some(1));
+
+    let Shape::Circle { radius } = Shape::circle_builder().radius(1).build().unwrap();
+    assert_eq!(radius, 1);
+}
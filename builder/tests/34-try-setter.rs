@@ -0,0 +1,63 @@
+// #[builder(try_setter)] on a field, or on the struct for every field, adds a
+// `try_` setter next to the regular one. It takes anything convertible into
+// the field through TryInto and returns the error of a failed conversion
+// instead of the builder, so that parsing input can be chained on it.
+//
+//     pub fn try_port<__V: TryInto<u16>>(
+//         &mut self,
+//         port: __V,
+//     ) -> Result<&mut Self, __V::Error>
+
+use derive_builder::Builder;
+use std::convert::TryFrom;
+use std::net::{AddrParseError, IpAddr};
+use std::num::TryFromIntError;
+
+// parses an address from a string, which `IpAddr` cannot do through TryFrom
+pub struct Parse<'a>(&'a str);
+
+impl TryFrom<Parse<'_>> for IpAddr {
+    type Error = AddrParseError;
+
+    fn try_from(value: Parse) -> Result<Self, Self::Error> {
+        value.0.parse()
+    }
+}
+
+#[derive(Builder, Debug)]
+pub struct Server {
+    #[builder(try_setter)]
+    ip: IpAddr,
+    #[builder(try_setter)]
+    port: u16,
+    #[builder(try_setter)]
+    backlog: Option<u8>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate, try_setter)]
+pub struct Limits {
+    max: u8,
+    min: Option<u8>,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let server = Server::builder()
+        .try_ip(Parse("127.0.0.1"))?
+        .try_port(8080u64)?
+        .try_backlog(16i32)?
+        .build()?;
+    assert_eq!(server.ip, IpAddr::from([127, 0, 0, 1]));
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.backlog, Some(16));
+
+    let mut builder = Server::builder();
+    assert!(builder.try_ip(Parse("localhost")).is_err());
+    let err: TryFromIntError = builder.try_port(-1).err().unwrap();
+    assert_eq!(err.to_string(), "out of range integral type conversion attempted");
+
+    let limits = Limits::builder().try_min(1u32)?.try_max(200u32)?.build();
+    assert_eq!((limits.min, limits.max), (Some(1), 200));
+    assert!(Limits::builder().try_max(300u32).is_err());
+    Ok(())
+}
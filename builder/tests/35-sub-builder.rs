@@ -0,0 +1,222 @@
+// #[builder(sub_builder)] on a field whose type itself derives Builder stores
+// the builder of that type instead, and adds a `<field>_mut` method giving
+// access to it, so that nested structures can be configured in place.
+//
+//     pub fn tls_mut(&mut self) -> &mut TlsBuilder
+//
+// The regular setter still takes a whole value. `build` builds the nested
+// builder, even if it was never used, unless the field has a default or is
+// an Option, which is left as `None` if its builder was never used. Errors
+// of nested builders are reported through a `Nested` variant of the error,
+// with the dotted path of the field which failed. Every generated error type
+// tells which field it is about through `field_path()`, and what went wrong
+// through `reason()`. A nested builder with #[builder(build_fn(error = "..."))]
+// fails with an error of the user's own type, which has to provide such
+// `field_path` and `reason` methods itself.
+//
+// The options of the nested builder cannot be seen from the field, so a
+// builder or build method with another name, or a pattern other than the
+// default one, are repeated through
+// #[builder(sub_builder(name = "...", build_fn = "...", pattern = "..."))].
+// A nested builder which cannot be built in place, like an owned one within a
+// mutable builder, is built from a clone of it, so it must derive Clone.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+pub struct Tls {
+    cert: String,
+    key: Option<String>,
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(build_fn(validate = "Limits::check"), derive(Clone))]
+pub struct Limits {
+    connections: u32,
+}
+
+impl Limits {
+    fn check(&self) -> Result<(), String> {
+        if self.connections == 0 {
+            return Err("no connections allowed".to_owned());
+        }
+        Ok(())
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits { connections: 64 }
+    }
+}
+
+#[derive(Builder, Debug)]
+pub struct Server {
+    host: String,
+    #[builder(sub_builder)]
+    tls: Tls,
+    #[builder(sub_builder, default)]
+    limits: Limits,
+}
+
+#[derive(Builder, Debug)]
+pub struct Proxy {
+    host: String,
+    #[builder(sub_builder)]
+    tls: Option<Tls>,
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(build_fn(name = "finish"))]
+pub struct Cert {
+    path: String,
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(name = "KeyOptions")]
+pub struct Key {
+    path: String,
+}
+
+#[derive(Debug)]
+pub struct ProxyError(String);
+
+impl ProxyError {
+    pub fn field_path(&self) -> Option<String> {
+        None
+    }
+
+    pub fn reason(&self) -> String {
+        self.0.clone()
+    }
+}
+
+impl From<UpstreamBuilderError> for ProxyError {
+    fn from(error: UpstreamBuilderError) -> Self {
+        ProxyError(format!("bad upstream: {}", error))
+    }
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(build_fn(error = "ProxyError"))]
+pub struct Upstream {
+    url: String,
+}
+
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(pattern = "owned", derive(Clone))]
+pub struct Retry {
+    attempts: u32,
+}
+
+#[derive(Builder, Debug)]
+pub struct Client {
+    #[builder(sub_builder(build_fn = "finish"))]
+    cert: Cert,
+    #[builder(sub_builder(name = "KeyOptions"))]
+    key: Key,
+    #[builder(sub_builder(pattern = "owned"))]
+    retry: Retry,
+    #[builder(sub_builder)]
+    upstream: Option<Upstream>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(pattern = "immutable")]
+pub struct Pool {
+    #[builder(sub_builder)]
+    limits: Limits,
+}
+
+#[derive(Builder, Debug)]
+pub struct App {
+    name: String,
+    #[builder(sub_builder)]
+    server: Server,
+}
+
+#[derive(Builder, Debug)]
+#[builder(pattern = "owned")]
+pub struct Job {
+    #[builder(sub_builder)]
+    limits: Limits,
+}
+
+fn main() {
+    let mut builder = App::builder();
+    builder.name("app".to_owned());
+    builder.server_mut().host("localhost".to_owned());
+    builder.server_mut().tls_mut().cert("cert.pem".to_owned());
+    let app = builder.build().unwrap();
+    assert_eq!(app.server.host, "localhost");
+    assert_eq!(app.server.tls.cert, "cert.pem");
+    assert_eq!(app.server.tls.key, None);
+    assert_eq!(app.server.limits, Limits::default());
+
+    // a whole value can still be set at once
+    let tls = Tls::builder().cert("a".to_owned()).key("b".to_owned()).build().unwrap();
+    let mut server = Server::builder();
+    server.host("localhost".to_owned()).tls(tls.clone());
+    server.limits_mut().connections(8);
+    let server = server.build().unwrap();
+    assert_eq!(server.tls, tls);
+    assert_eq!(server.limits.connections, 8);
+
+    let mut builder = App::builder();
+    builder.name("app".to_owned());
+    builder.server_mut().host("localhost".to_owned());
+    let err = builder.build().unwrap_err();
+    assert_eq!(
+        err,
+        AppBuilderError::Nested {
+            path: "server.tls.cert".to_owned(),
+            message: "missing field".to_owned(),
+        },
+    );
+    assert_eq!(err.to_string(), "`server.tls.cert`: missing field");
+    assert_eq!(err.field_path().as_deref(), Some("server.tls.cert"));
+
+    builder.server_mut().tls_mut().cert("cert.pem".to_owned());
+    builder.server_mut().limits_mut().connections(0);
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.to_string(), "`server.limits`: no connections allowed");
+    assert_eq!(err.reason(), "no connections allowed");
+
+    let mut proxy = Proxy::builder();
+    proxy.host("localhost".to_owned());
+    assert_eq!(proxy.build().unwrap().tls, None);
+    proxy.tls_mut().key("key.pem".to_owned());
+    assert_eq!(
+        proxy.build().unwrap_err().to_string(),
+        "`tls.cert`: missing field",
+    );
+    proxy.tls_mut().cert("cert.pem".to_owned());
+    assert_eq!(proxy.build().unwrap().tls.unwrap().cert, "cert.pem");
+
+    let mut client = Client::builder();
+    client.cert_mut().path("cert.pem".to_owned());
+    client.key_mut().path("key.pem".to_owned());
+    assert_eq!(
+        client.build().unwrap_err().to_string(),
+        "`retry.attempts`: missing field",
+    );
+    client.retry(Retry { attempts: 3 });
+    client.upstream_mut();
+    assert_eq!(
+        client.build().unwrap_err().to_string(),
+        "`upstream`: bad upstream: missing field `url`",
+    );
+    client.upstream_mut().url("http://a".to_owned());
+    let client = client.build().unwrap();
+    assert_eq!(client.cert.path, "cert.pem");
+    assert_eq!(client.key.path, "key.pem");
+    assert_eq!(client.retry.attempts, 3);
+
+    let mut pool = Pool::builder();
+    pool.limits_mut().connections(4);
+    assert_eq!(pool.build().unwrap().limits.connections, 4);
+
+    let mut job = Job::builder();
+    job.limits_mut().connections(2);
+    assert_eq!(job.build().unwrap().limits.connections, 2);
+}
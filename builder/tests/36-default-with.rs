@@ -0,0 +1,89 @@
+// #[builder(default_with = "...")] names a fallible function computing a
+// field when its setter was never called. It returns Result<T, String>, and a
+// failure is reported by `build` through the `DefaultWith` variant of the
+// error.
+//
+// With #[builder(build_fn(context = "..."))] on the struct, `build()` becomes
+// `build_with(ctx)`, taking a reference to a context which is passed on to
+// every `default_with` function, e.g. to resolve values through a service.
+//
+//     pub fn build_with(&mut self, ctx: &Ctx) -> Result<Server, ServerBuilderError>
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+
+fn read_token() -> Result<String, String> {
+    Err("token file not found".to_owned())
+}
+
+#[derive(Builder, Debug)]
+pub struct Client {
+    url: String,
+    #[builder(default_with = "read_token")]
+    token: String,
+}
+
+pub trait Resolver {
+    fn resolve(&self, host: &str) -> Option<[u8; 4]>;
+}
+
+pub struct Hosts(HashMap<&'static str, [u8; 4]>);
+
+impl Resolver for Hosts {
+    fn resolve(&self, host: &str) -> Option<[u8; 4]> {
+        self.0.get(host).copied()
+    }
+}
+
+fn resolve_db(resolver: &dyn Resolver) -> Result<[u8; 4], String> {
+    resolver
+        .resolve("db")
+        .ok_or_else(|| "cannot resolve `db`".to_owned())
+}
+
+fn default_port(_: &dyn Resolver) -> Result<Option<u16>, String> {
+    Ok(Some(5432))
+}
+
+#[derive(Builder, Debug)]
+#[builder(build_fn(context = "dyn Resolver"))]
+pub struct Server {
+    // does not shadow the context given to `build_with`
+    #[builder(default)]
+    ctx: u8,
+    #[builder(default_with = "resolve_db")]
+    db: [u8; 4],
+    #[builder(default_with = "default_port")]
+    port: Option<u16>,
+}
+
+fn main() {
+    let client = Client::builder()
+        .url("http://a".to_owned())
+        .token("secret".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(client.token, "secret");
+
+    let err = Client::builder().url("http://a".to_owned()).build().unwrap_err();
+    assert_eq!(
+        err,
+        ClientBuilderError::DefaultWith {
+            field: "token",
+            message: "token file not found".to_owned(),
+        },
+    );
+    assert_eq!(err.to_string(), "`token`: token file not found");
+
+    let hosts = Hosts(HashMap::from([("db", [10, 0, 0, 1])]));
+    let server = Server::builder().build_with(&hosts).unwrap();
+    assert_eq!(server.db, [10, 0, 0, 1]);
+    assert_eq!(server.port, Some(5432));
+
+    let server = Server::builder().port(1).build_with(&hosts).unwrap();
+    assert_eq!(server.port, Some(1));
+
+    let err = Server::builder().build_with(&Hosts(HashMap::new())).unwrap_err();
+    assert_eq!(err.field_path().as_deref(), Some("db"));
+    assert_eq!(err.reason(), "cannot resolve `db`");
+}
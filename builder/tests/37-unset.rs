@@ -0,0 +1,119 @@
+// With #[builder(introspect)] every field of the builder can be set back to
+// unset through a `clear_<field>` method, and asked about through
+// `<field>_is_set`. `missing_fields` lists the mandatory fields which are not
+// set yet, in declaration order, e.g. for an interactive tool to prompt for
+// exactly those before calling `build`. Fields with a builder of their own
+// through #[builder(sub_builder)] are not listed, their builders tell which of
+// their fields are missing.
+//
+//     pub fn clear_current_dir(&mut self) -> &mut Self
+//     pub fn current_dir_is_set(&self) -> bool
+//     pub fn missing_fields(&self) -> Vec<&'static str>
+//
+// A typestate builder tracks mandatory fields in its type, so only its other
+// fields get these methods.
+//
+// Without the option none of these methods are generated, so fields may have
+// setters of the same names.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(introspect)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    r#type: String,
+    current_dir: Option<String>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(pattern = "owned", introspect)]
+pub struct Owned {
+    name: String,
+    #[builder(default)]
+    id: u32,
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate, introspect)]
+pub struct Typed {
+    name: String,
+    current_dir: Option<String>,
+}
+
+#[derive(Builder, Debug)]
+#[builder(introspect)]
+pub struct Tls {
+    cert: String,
+}
+
+#[derive(Builder, Debug)]
+#[builder(introspect)]
+pub struct Server {
+    host: String,
+    #[builder(sub_builder)]
+    tls: Tls,
+}
+
+#[derive(Builder, Debug)]
+pub struct Form {
+    missing_fields: Vec<String>,
+    name: String,
+    clear_name: bool,
+    name_is_set: bool,
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    assert_eq!(builder.missing_fields(), vec!["executable", "type"]);
+    assert!(!builder.executable_is_set());
+
+    builder.executable("cargo".to_owned()).r#type("bin".to_owned());
+    assert!(builder.executable_is_set());
+    assert!(builder.type_is_set());
+    assert!(builder.missing_fields().is_empty());
+
+    builder.clear_type().arg("build".to_owned());
+    assert!(builder.args_is_set());
+    assert_eq!(builder.missing_fields(), vec!["type"]);
+    assert!(builder.build().is_err());
+
+    builder.current_dir("..".to_owned()).clear_current_dir();
+    assert!(!builder.current_dir_is_set());
+
+    let command = builder.r#type("bin".to_owned()).clear_args().build().unwrap();
+    assert!(command.args.is_empty());
+    assert_eq!(command.current_dir, None);
+
+    let builder = Owned::builder().name("x".to_owned()).id(1).clear_id();
+    assert!(!builder.id_is_set());
+    assert!(builder.missing_fields().is_empty());
+    assert_eq!(builder.clear_name().missing_fields(), vec!["name"]);
+
+    let builder = Typed::builder().current_dir("..".to_owned());
+    assert!(builder.current_dir_is_set());
+    let typed = builder.clear_current_dir().name("x".to_owned()).build();
+    assert_eq!(typed.current_dir, None);
+
+    let mut server = Server::builder();
+    server.host("localhost".to_owned());
+    assert!(server.missing_fields().is_empty());
+    assert!(!server.tls_is_set());
+    assert_eq!(
+        server.build().unwrap_err().to_string(),
+        "`tls.cert`: missing field",
+    );
+    assert_eq!(server.tls_mut().missing_fields(), vec!["cert"]);
+
+    let form = Form::builder()
+        .missing_fields(vec!["age".to_owned()])
+        .name("x".to_owned())
+        .clear_name(true)
+        .name_is_set(false)
+        .build()
+        .unwrap();
+    assert_eq!(form.missing_fields, vec!["age"]);
+    assert!(form.clear_name);
+}
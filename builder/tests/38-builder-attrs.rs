@@ -0,0 +1,61 @@
+// #[builder(derive(...))] derives the given traits for the builder, e.g. so it
+// can be cloned, compared and printed.
+//
+// #[builder(struct_attr(...))] puts arbitrary attributes on the builder
+// struct, and #[builder(setter_attr(...))] on its setters, given either on the
+// struct for every setter or on a field for the setters of that field.
+//
+//     #[derive(Clone, Debug, PartialEq)]
+//     #[must_use = "a builder does nothing until built"]
+//     pub struct CommandBuilder { ... }
+//
+//     #[inline]
+//     pub fn executable(&mut self, executable: String) -> &mut Self
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(
+    derive(Clone, Debug, PartialEq),
+    struct_attr(must_use = "a builder does nothing until built", non_exhaustive),
+    setter_attr(inline)
+)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg", setter_attr(doc(alias = "argument")))]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(typestate, derive(Clone, Debug, PartialEq), struct_attr(must_use))]
+pub struct Request<T> {
+    #[builder(setter_attr(inline, must_use))]
+    body: T,
+    #[builder(default)]
+    retries: u32,
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    builder.executable("cargo".to_owned()).arg("build".to_owned());
+    let copy = builder.clone();
+    assert_eq!(builder, copy);
+    assert!(format!("{:?}", copy).starts_with("CommandBuilder {"));
+
+    builder.current_dir("..".to_owned());
+    assert_ne!(builder, copy);
+
+    let command = copy.clone().build().unwrap();
+    assert_eq!(command.args, vec!["build"]);
+
+    let builder = Request::builder().body(vec![1u8]);
+    assert_eq!(builder.clone(), builder);
+    assert_eq!(
+        builder.retries(2).build(),
+        Request {
+            body: vec![1],
+            retries: 2,
+        },
+    );
+}
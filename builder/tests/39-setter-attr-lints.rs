@@ -0,0 +1,20 @@
+// Attributes from #[builder(setter_attr(...))] end up on the generated
+// setters, so the usual lints apply to calls of them.
+
+#![deny(deprecated)]
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+pub struct Command {
+    executable: String,
+    #[builder(setter_attr(deprecated(note = "pass the directory to `run` instead")))]
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let _ = Command::builder()
+        .executable("cargo".to_owned())
+        .current_dir("..".to_owned())
+        .build();
+}
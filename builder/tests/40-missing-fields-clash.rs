@@ -0,0 +1,14 @@
+// With #[builder(build_fn(collect_missing))] the error reports missing fields
+// through `MissingFields`, so a mandatory field named `fields` cannot have a
+// variant of its own by that name. This is reported at the field.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(build_fn(collect_missing))]
+pub struct Query {
+    table: String,
+    fields: Vec<String>,
+}
+
+fn main() {}
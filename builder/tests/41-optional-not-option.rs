@@ -0,0 +1,14 @@
+// #[builder(optional)] leaves a field as `None` when its setter was never
+// called, so the field's type must be an Option, possibly behind an alias.
+// On any other type the mismatch is reported at the option.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(optional)]
+    current_dir: String,
+}
+
+fn main() {}
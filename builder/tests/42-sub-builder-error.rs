@@ -0,0 +1,29 @@
+// The nested builder of a #[builder(sub_builder)] field is asked what failed
+// through the `field_path` and `reason` methods of its error. An error type
+// given through #[builder(build_fn(error = "..."))] without them is reported
+// at the field.
+
+use derive_builder::Builder;
+
+#[derive(Debug)]
+pub struct TlsError;
+
+impl From<TlsBuilderError> for TlsError {
+    fn from(_: TlsBuilderError) -> Self {
+        TlsError
+    }
+}
+
+#[derive(Builder, Debug)]
+#[builder(build_fn(error = "TlsError"))]
+pub struct Tls {
+    cert: String,
+}
+
+#[derive(Builder, Debug)]
+pub struct Server {
+    #[builder(sub_builder)]
+    tls: Tls,
+}
+
+fn main() {}
@@ -10,4 +10,37 @@ fn tests() {
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-multiple-structs.rs");
+    t.pass("tests/11-generics.rs");
+    t.pass("tests/12-error-type.rs");
+    t.pass("tests/13-collect-missing.rs");
+    t.pass("tests/14-default.rs");
+    t.pass("tests/15-typestate.rs");
+    t.compile_fail("tests/16-typestate-missing-field.rs");
+    t.pass("tests/17-patterns.rs");
+    t.pass("tests/18-setter-into.rs");
+    t.pass("tests/19-validate.rs");
+    t.pass("tests/20-each-collections.rs");
+    t.pass("tests/21-tuple-structs-and-enums.rs");
+    t.compile_fail("tests/22-union.rs");
+    t.pass("tests/23-visibility.rs");
+    t.compile_fail("tests/24-private-setter.rs");
+    t.pass("tests/25-skip.rs");
+    t.pass("tests/26-custom-names.rs");
+    t.pass("tests/27-repeated-attributes.rs");
+    t.compile_fail("tests/28-attribute-errors.rs");
+    t.pass("tests/29-qualified-paths.rs");
+    t.pass("tests/30-from-value.rs");
+    t.pass("tests/31-no-std.rs");
+    t.pass("tests/32-serde.rs");
+    t.pass("tests/33-docs.rs");
+    t.pass("tests/34-try-setter.rs");
+    t.pass("tests/35-sub-builder.rs");
+    t.pass("tests/36-default-with.rs");
+    t.pass("tests/37-unset.rs");
+    t.pass("tests/38-builder-attrs.rs");
+    t.compile_fail("tests/39-setter-attr-lints.rs");
+    t.compile_fail("tests/40-missing-fields-clash.rs");
+    t.compile_fail("tests/41-optional-not-option.rs");
+    t.compile_fail("tests/42-sub-builder-error.rs");
 }